@@ -15,10 +15,31 @@ pub mod apks;
 pub mod config;
 pub mod updater;
 pub mod orientation;
-pub mod layout_bounds;
 pub mod system;
-pub mod taps;
-pub mod pointer;
 pub mod cmd_editor;
 pub mod sdk;
-pub mod anim_scale;
+pub mod mp4;
+pub mod flags;
+pub mod convert;
+pub mod record_options;
+pub mod logcat;
+pub mod hook;
+pub mod activity;
+pub mod template;
+pub mod demo;
+pub mod axml;
+pub mod splits;
+pub mod launch;
+pub mod packages;
+pub mod lifecycle;
+pub mod appdata;
+pub mod perms;
+pub mod install_error;
+pub mod signing;
+pub mod apkinfo;
+pub mod clone_app;
+pub mod apps;
+pub mod logview;
+pub mod crashwatch;
+pub mod toggles;
+pub mod profile;
@@ -0,0 +1,52 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::ext::OutputExt;
+use crate::core::r#const::SHELL;
+use crate::core::selector::run_adb_with;
+use regex::Regex;
+
+const RESUMED_ACTIVITY: &str = "dumpsys activity activities | grep -E 'mResumedActivity|topResumedActivity'";
+const FOCUSED_WINDOW: &str = "dumpsys window | grep -E 'mCurrentFocus|mFocusedApp'";
+const ROTATION: &str = "dumpsys input | grep -m1 -E 'SurfaceOrientation|orientation='";
+// ActivityRecord{f1d5e3c u0 com.android.settings/.Settings t12}
+const COMPONENT: &str = r"\s([\w.]+)/([\w.$]+)[\s}]";
+
+pub struct Activity {
+    pub package: String,
+    /// as in the dump, maybe `.Main`
+    pub class: String,
+}
+
+/// The package of the activity on the top of the screen, if any.
+pub fn focused_package(device: &AdbDevice) -> Option<String> {
+    return focused_activity(device).map(|it| it.package)
+}
+
+/// The activity on the top of the screen, if any.
+pub fn focused_activity(device: &AdbDevice) -> Option<Activity> {
+    let pattern = Regex::new(COMPONENT).unwrap();
+    for command in [RESUMED_ACTIVITY, FOCUSED_WINDOW] {
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, command])).stdout();
+        let found = output.lines()
+            .filter_map(|line| pattern.captures(line))
+            .map(|it| Activity { package: it[1].to_string(), class: it[2].to_string() })
+            .next();
+        if found.is_some() {
+            return found
+        }
+    }
+    return None
+}
+
+/// The display rotation in quarter turns.
+pub fn rotation(device: &AdbDevice) -> Option<u8> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, ROTATION])).stdout();
+    // SurfaceOrientation: 1 or orientation=ROTATION_90
+    let value = Regex::new(r"(?:SurfaceOrientation: |ROTATION_)(\d+)").unwrap()
+        .captures(&output)?[1]
+        .parse::<u16>().ok()?;
+    return match value {
+        0..=3 => Some(value as u8),
+        _ => Some((value / 90 % 4) as u8),
+    }
+}
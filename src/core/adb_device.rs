@@ -1,6 +1,8 @@
 pub struct AdbDevice {
     pub serial: String,
+    pub description: String,
     pub model: String,
+    pub sdk: Option<usize>,
     pub ok: bool,
     pub unauthorized: bool,
     pub no_permissions: bool,
@@ -14,14 +16,14 @@ impl AdbDeviceVec for Vec<AdbDevice> {
     fn get_unique_model_name(&self, device: &AdbDevice) -> String {
         let mut count = 0;
         for d in self {
-            if d.model == device.model {
+            if d.description == device.description {
                 count += 1;
             }
         }
         return if count > 1 {
-            format!("{} ({})", device.model, device.serial)
+            format!("{} ({})", device.description, device.serial)
         } else {
-            device.model.clone()
+            device.description.clone()
         }
     }
 }
@@ -0,0 +1,200 @@
+use crate::core::apks::{apks_in, get_version, package_paths, pull};
+use crate::core::axml::Manifest;
+use crate::core::destination::Destination;
+use crate::core::ext::{PrintExt, ResultExt};
+use crate::core::selector::{fetch_adb_devices, resolve_device};
+use crate::core::signing::{signature, Signature};
+use crate::core::strings::*;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use zip::ZipArchive;
+
+const USAGE_TEXT: &str = "apkinfo app.apk|app.package.name";
+const LIB: &str = "lib/";
+const BASE_APK: &str = "base.apk";
+const NONE: &str = "-";
+
+/// What `apkinfo` shows, the splits add their ABIs.
+pub struct ApkInfo {
+    pub manifest: Manifest,
+    pub abis: Vec<String>,
+    pub signature: Option<Signature>,
+}
+
+impl ApkInfo {
+    /// The base APK is the one without a split name.
+    pub fn read(apks: &[PathBuf]) -> Result<ApkInfo, String> {
+        let mut base = None;
+        let mut abis = vec![];
+        for apk in apks {
+            let manifest = Manifest::from_apk(apk)?;
+            for abi in abis_of(apk)? {
+                if !abis.contains(&abi) {
+                    abis.push(abi);
+                }
+            }
+            if manifest.split.is_none() || base.is_none() {
+                base = Some((apk, manifest));
+            }
+        }
+        let (path, manifest) = base.ok_or_else(|| NO_APKS.value().to_string())?;
+        abis.sort();
+        return Ok(ApkInfo { manifest, abis, signature: signature(path)? })
+    }
+
+    fn print(&self) {
+        let manifest = &self.manifest;
+        let or_none = |value: Option<u32>| value.map(|it| it.to_string()).unwrap_or(NONE.to_string());
+        APK_PACKAGE.println_formatted(&[&manifest.package]);
+        APK_VERSION.println_formatted(&[
+            manifest.version_name.as_deref().unwrap_or(NONE),
+            &manifest.version_code.map(|it| it.to_string()).unwrap_or(NONE.to_string()),
+        ]);
+        APK_SDK.println_formatted(&[&or_none(manifest.min_sdk), &or_none(manifest.target_sdk), &or_none(manifest.compile_sdk)]);
+        let abis = match self.abis.is_empty() {
+            true => NONE.to_string(),
+            false => self.abis.join(", "),
+        };
+        APK_ABIS.println_formatted(&[&abis]);
+        match &self.signature {
+            Some(signature) => APK_SIGNATURE.println_formatted(&[signature.scheme, &signature.sha256]),
+            None => APK_UNSIGNED.println(),
+        }
+        print_list(&APK_LAUNCHERS, &manifest.launchers);
+        print_list(&APK_PERMISSIONS, &manifest.permissions);
+    }
+}
+
+/// The `apkinfo` command for a local APK or an installed package.
+pub fn apkinfo(args: &[String]) -> ExitCode {
+    let target = match args.first() {
+        Some(target) => target,
+        None => {
+            USAGE.formatted(&[USAGE_TEXT]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let path = target.dst();
+    return match path.is_file() {
+        true => local(&path),
+        false => installed(target),
+    }
+}
+
+/// With a device the APK is compared with the installed app.
+fn local(apk: &Path) -> ExitCode {
+    let info = match ApkInfo::read(&[apk.to_path_buf()]) {
+        Ok(info) => info,
+        Err(e) => {
+            format!("{}: {e}", apk.to_string_lossy()).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    info.print();
+    if !fetch_adb_devices().iter().any(|it| it.ok) {
+        return ExitCode::SUCCESS
+    }
+    return compare(&info)
+}
+
+fn installed(package: &str) -> ExitCode {
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let paths = match package_paths(&device, package) {
+        Ok(paths) => paths,
+        Err(code) => return code,
+    };
+    let tmp = match tempfile::tempdir() {
+        Ok(tmp) => tmp,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    if let Err(code) = pull(&device, &paths, tmp.path()) {
+        return code
+    }
+    let info = apks_in(tmp.path()).and_then(|it| ApkInfo::read(&it));
+    return match info {
+        Ok(info) => {
+            info.print();
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            format!("{package}: {e}").eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn compare(info: &ApkInfo) -> ExitCode {
+    let package = &info.manifest.package;
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let paths = match package_paths(&device, package) {
+        Ok(paths) => paths,
+        Err(_) => return ExitCode::SUCCESS,
+    };
+    let version = get_version(&device, package);
+    APK_INSTALLED.println_formatted(&[
+        version.name.as_deref().unwrap_or(NONE),
+        version.code.as_deref().unwrap_or(NONE),
+    ]);
+    let installed_code = version.code.and_then(|it| it.parse::<u64>().ok());
+    if let (Some(local), Some(installed)) = (info.manifest.version_code, installed_code) {
+        match local.cmp(&installed) {
+            Ordering::Greater => APK_NEWER.println(),
+            Ordering::Equal => APK_SAME_VERSION.println(),
+            Ordering::Less => APK_OLDER.println(),
+        }
+    }
+    let base = paths.iter()
+        .find(|it| it.ends_with(BASE_APK))
+        .unwrap_or(&paths[0])
+        .clone();
+    let tmp = match tempfile::tempdir() {
+        Ok(tmp) => tmp,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let dst = tmp.path().join(BASE_APK);
+    if let Err(code) = pull(&device, &[base], &dst) {
+        return code
+    }
+    let installed = signature(&dst).soft_unwrap().flatten();
+    match (&info.signature, installed) {
+        (Some(local), Some(installed)) if local.sha256 == installed.sha256 => SIGNATURE_MATCHES.println(),
+        (None, None) => BOTH_UNSIGNED.println(),
+        (_, Some(installed)) => SIGNATURE_DIFFERS.println_formatted(&[&installed.sha256]),
+        (Some(_), None) => INSTALLED_UNSIGNED.println(),
+    }
+    return ExitCode::SUCCESS
+}
+
+/// "lib/arm64-v8a/libapp.so" gives "arm64-v8a".
+fn abis_of(apk: &Path) -> Result<Vec<String>, String> {
+    let zip = ZipArchive::new(File::open(apk).string_err()?).string_err()?;
+    let abis = zip.file_names()
+        .filter_map(|it| it.strip_prefix(LIB)?.split_once('/'))
+        .map(|(abi, _)| abi.to_string())
+        .collect::<Vec<_>>();
+    return Ok(abis)
+}
+
+fn print_list(title: &Label, items: &[String]) {
+    if items.is_empty() {
+        return
+    }
+    title.println();
+    for item in items {
+        format!("  {item}").println();
+    }
+}
@@ -1,122 +1,488 @@
 use crate::core::adb_command::AdbArgs;
 use crate::core::adb_device::AdbDevice;
+use crate::core::axml::Manifest;
 use crate::core::config::Config;
 use crate::core::destination::Destination;
-use crate::core::ext::{OutputExt, PathBufExt, PrintExt, ResultExt, StrExt};
-use crate::core::r#const::{INSTALL, PULL, SHELL};
-use crate::core::selector::{resolve_device, run_adb_with};
-use crate::core::strings::{NO_ANDROID_SDK, NO_BUILD_TOOLS, NO_FILE, NO_PACKAGE_NAME, NO_PATH, SAVED};
-use crate::core::system::config_path;
-use crate::core::util::string;
+use crate::core::ext::{OutputExt, PathBufExt, PrintExt, ResultExt};
+use crate::core::flags::Flags;
+use crate::core::install_error::{InstallError, InstallFix};
+use crate::core::launch;
+use crate::core::launch::{launch_app, LaunchOptions};
+use crate::core::packages::{package_or_pick, PackageFilter};
+use crate::core::r#const::{INSTALL, INSTALL_MULTIPLE, NULL, PULL, SHELL};
+use crate::core::selector::{adb_args_with, resolve_device, run_adb_with};
+use crate::core::splits::DeviceSpec;
+use crate::core::strings::{BUILDING_APKS, INVALID_MANIFEST, INVALID_OPTION_VALUE, NOT_INSTALLED, NO_APKS, NO_BUNDLETOOL, NO_FILE, NO_JAVA, NO_PATH, SAVED, VERSION_NAME, CHANGED, TIMINGS, WATCHING, FREE_SPACE};
+use crate::core::util::interactive_confirm;
+use crate::core::system::{catch_interrupts, config_path, error_exit_status, interrupted, release_interrupts};
 use regex::Regex;
+use std::env;
 use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitCode, Output};
+use std::process::{Command, ExitCode, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
-pub fn steal_apk(package: Option<String>, dst: Option<String>) -> ExitCode {
-    let package = match package {
-        Some(package) => package,
-        None => {
-            NO_PACKAGE_NAME.eprintln();
+const BUNDLE: &str = "bundle";
+const APK: &str = "apk";
+const APKS: &str = "apks";
+const APKM: &str = "apkm";
+const XAPK: &str = "xapk";
+const AAB: &str = "aab";
+const BUNDLES: &[&str] = &[APKS, APKM, XAPK];
+// the pre-Lollipop APKs of bundletool, the splits cover the rest
+const STANDALONES: &str = "standalones";
+const JAVA: &str = "java";
+const JAVA_HOME: &str = "JAVA_HOME";
+const PACKAGE_PREFIX: &str = "package:";
+const APKM_INFO: &str = "info.json";
+const GRANT_ALL: &str = "grant-all";
+const GRANT_ALL_FLAG: &str = "-g";
+const WATCH: &str = "watch";
+const REINSTALL_FLAG: &str = "-r";
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+const DEBOUNCE: Duration = Duration::from_secs(1);
+const FREE_SPACE_COMMAND: &str = "df -h /data";
+
+pub struct Version {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub installer: Option<String>,
+}
+
+pub fn steal_apk(args: &[String]) -> ExitCode {
+    let flags = match Flags::parse(args, &[], &[BUNDLE]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
             return ExitCode::FAILURE
         }
     };
-    let pm_command = format!("pm path {package}");
-    let args = AdbArgs::run(&[SHELL, pm_command.as_str()]);
+    let bundle = flags.value(BUNDLE);
+    if let Some(bundle) = bundle.as_ref().filter(|it| !BUNDLES.contains(&it.as_str())) {
+        INVALID_OPTION_VALUE.formatted(&[&format!("--{BUNDLE}"), bundle]).eprintln();
+        return ExitCode::FAILURE
+    }
     let device = match resolve_device() {
         Ok(device) => device,
         Err(code) => return code,
     };
-    let output = run_adb_with(&device, args);
+    let package = match package_or_pick(&device, flags.arg(0), &PackageFilter::default()) {
+        Ok(package) => package,
+        Err(code) => return code,
+    };
+    let paths = match package_paths(&device, &package) {
+        Ok(paths) => paths,
+        Err(code) => return code,
+    };
+    let version = get_version(&device, &package);
+    if let Some(name) = &version.name {
+        VERSION_NAME.println_formatted(&[name]);
+    }
+    let dst = flags.arg(1).unwrap_or_default().dst();
+    let result = match (paths.as_slice(), bundle) {
+        ([path], None) => pull(&device, std::slice::from_ref(path), &dst.join(format!("{package}.{APK}"))),
+        (_, None) => pull_splits(&device, &paths, &dst.join(&package)),
+        (_, Some(bundle)) => pull_bundle(&device, &paths, &package, &version, &dst.join(format!("{package}.{bundle}"))),
+    };
+    return match result {
+        Ok(path) => {
+            SAVED.println_formatted(&[&path.to_string()]);
+            ExitCode::SUCCESS
+        },
+        Err(code) => code,
+    }
+}
+
+/// The paths of the base APK and the splits.
+pub fn package_paths(device: &AdbDevice, package: &str) -> Result<Vec<String>, ExitCode> {
+    let pm_command = format!("pm path {package}");
+    let args = AdbArgs::run(&[SHELL, pm_command.as_str()]);
+    let output = run_adb_with(device, args);
     if !output.status.success() {
         output.print_err();
-        return output.exit_code()
+        return Err(output.exit_code())
+    }
+    // a line per split "package:/data/app/[…]/base.apk"
+    let paths = output.stdout()
+        .lines()
+        .filter_map(|it| it.trim().strip_prefix(PACKAGE_PREFIX))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        NOT_INSTALLED.formatted(&[package]).eprintln();
+        return Err(ExitCode::FAILURE)
     }
-    let destination = dst
-        .unwrap_or(string(""))
-        .dst()
-        .join(format!("{package}.apk"));
-    // the output line is "package:/data/data/[…]/base.apk"
-    let path = &output.stdout().clone()[8..];
-    let args = AdbArgs::spawn(&[PULL, path, destination.to_str()]);
-    let output = run_adb_with(&device, args);
-    if output.status.success() {
-        SAVED.println_formatted(&[&destination.to_string()]);
+    return Ok(paths)
+}
+
+pub fn get_version(device: &AdbDevice, package: &str) -> Version {
+    let command = format!("dumpsys package {package}");
+    let text = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()])).stdout();
+    // the first entry is the installed one, an updated system app has two of them
+    let find = |pattern: &str| Regex::new(pattern).unwrap()
+        .captures(&text)
+        .map(|it| it[1].to_string());
+    return Version {
+        code: find(r"versionCode=(\d+)"),
+        name: find(r"versionName=(\S+)"),
+        installer: find(r"installerPackageName=(\S+)").filter(|it| it != NULL),
     }
-    return output.exit_code()
 }
 
-pub fn run_apk(apk: String, config: &Config)-> ExitCode {
+pub fn pull(device: &AdbDevice, paths: &[String], dst: &Path) -> Result<PathBuf, ExitCode> {
+    let args = [&[PULL.to_string()], paths, &[dst.to_string_lossy().to_string()]].concat();
+    let output = run_adb_with(device, AdbArgs::spawn(&args));
+    return match output.status.success() {
+        true => Ok(dst.to_path_buf()),
+        false => Err(output.exit_code()),
+    }
+}
+
+fn pull_splits(device: &AdbDevice, paths: &[String], dir: &Path) -> Result<PathBuf, ExitCode> {
+    fs::create_dir_all(dir).map_err(|e| {
+        e.eprintln();
+        ExitCode::FAILURE
+    })?;
+    return pull(device, paths, dir)
+}
+
+fn pull_bundle(device: &AdbDevice, paths: &[String], package: &str, version: &Version, dst: &Path) -> Result<PathBuf, ExitCode> {
+    let tmp = tempfile::tempdir().map_err(|e| {
+        e.eprintln();
+        ExitCode::FAILURE
+    })?;
+    let dir = tmp.path();
+    pull(device, paths, dir)?;
+    let info = match dst.extension().and_then(|it| it.to_str()) {
+        Some(APKM) => Some(serde_json::json!({
+            "pname": package,
+            "versioncode": version.code,
+            "release_version": version.name,
+        })),
+        _ => None,
+    };
+    return write_bundle(dir, info, dst).map_err(|e| {
+        e.eprintln();
+        ExitCode::FAILURE
+    })
+}
+
+fn write_bundle(dir: &Path, info: Option<serde_json::Value>, dst: &Path) -> Result<PathBuf, String> {
+    let mut apks = fs::read_dir(dir).string_err()?
+        .filter_map(Result::ok)
+        .map(|it| it.path())
+        .collect::<Vec<_>>();
+    apks.sort();
+    let mut zip = ZipWriter::new(File::create(dst).string_err()?);
+    // the splits are compressed already
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for apk in apks {
+        let name = apk.file_name().unwrap().to_string_lossy();
+        zip.start_file(name, options).string_err()?;
+        io::copy(&mut File::open(&apk).string_err()?, &mut zip).string_err()?;
+    }
+    if let Some(info) = info {
+        zip.start_file(APKM_INFO, options).string_err()?;
+        serde_json::to_writer(&mut zip, &info).string_err()?;
+    }
+    zip.finish().string_err()?;
+    return Ok(dst.to_path_buf())
+}
+
+pub fn run_apk(args: &[String], config: &Config)-> ExitCode {
+    let switches = [launch::SWITCHES.as_slice(), &[GRANT_ALL, WATCH]].concat();
+    let flags = match Flags::parse(args, &switches, &launch::OPTIONS) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let options = match LaunchOptions::from_flags(&flags) {
+        Ok(options) => options,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let apk = flags.arg(0).unwrap_or_default();
     if apk.is_empty() {
         NO_PATH.eprintln();
         return ExitCode::FAILURE;
     }
-    if !Path::new(&apk).exists() {
+    let path = Path::new(&apk);
+    if !path.exists() {
         NO_FILE.eprintln();
         return ExitCode::FAILURE;
     }
-    let aapt = match get_aapt(&config) {
-        Ok(path) => path,
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let mut install_flags = vec![];
+    if flags.has(GRANT_ALL) {
+        install_flags.push(GRANT_ALL_FLAG);
+    }
+    let watch_mode = flags.has(WATCH);
+    let code = install_and_launch(&device, path, config, &install_flags, &options, watch_mode);
+    if !watch_mode {
+        return code
+    }
+    install_flags.push(REINSTALL_FLAG);
+    return watch(path, || install_and_launch(&device, path, config, &install_flags, &options, true))
+}
+
+/// `timed` prints how long the installation and the launch took.
+fn install_and_launch(
+    device: &AdbDevice,
+    path: &Path,
+    config: &Config,
+    install_flags: &[&str],
+    options: &LaunchOptions,
+    timed: bool,
+) -> ExitCode {
+    // keeps the extracted splits until the installation is done
+    let tmp = match tempfile::tempdir() {
+        Ok(tmp) => tmp,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        },
+    };
+    let apks = collect_apks(path, tmp.path(), config)
+        .and_then(read_manifests);
+    let (apks, manifest) = match apks {
+        Ok(apks) => apks,
         Err(err) => {
-            err.eprintln();
+            format!("{}: {err}", path.to_string_lossy()).eprintln();
             return ExitCode::FAILURE
         },
     };
-    let device = match resolve_device() {
-        Ok(device) => device,
-        Err(code) => return code,
+    let apks = match apks.len() {
+        1 => apks,
+        _ => DeviceSpec::fetch(device).select(apks),
     };
-    let output = install(&device, &apk);
+    let apks = apks.into_iter().map(|(apk, _)| apk).collect::<Vec<_>>();
+    let start = Instant::now();
+    let output = install_with_fixes(device, &apks, &manifest.package, install_flags);
     if !output.status.success() {
         return output.exit_code()
     }
-    let (package, activity) = get_package_activity(aapt, &apk);
-    let output = launch(&device, package, activity);
-    return output.exit_code()
+    let installed = start.elapsed();
+    let code = launch_app(device, &manifest.package, manifest.launcher(), options);
+    if timed {
+        TIMINGS.println_formatted(&[&seconds(installed), &seconds(start.elapsed() - installed)]);
+    }
+    return code
 }
 
-fn get_aapt(config: &Config) -> Result<PathBuf, String> {
-    let path = match config.build_tools() {
-        None => return Err(NO_ANDROID_SDK.formatted(&[&config_path().to_string()])),
-        Some(path) => path,
+/// Reinstalls on every change of the APK, a split dir or a bundle until Ctrl-C.
+fn watch<F: FnMut() -> ExitCode>(path: &Path, mut deploy: F) -> ExitCode {
+    WATCHING.println_formatted(&[&path.to_string_lossy()]);
+    catch_interrupts();
+    let mut last = signature(path);
+    while !interrupted() {
+        thread::sleep(WATCH_INTERVAL);
+        let current = signature(path);
+        if current.is_none() || current == last {
+            continue
+        }
+        // the build writes the file for a while, wait until it stops changing
+        thread::sleep(DEBOUNCE);
+        if interrupted() || signature(path) != current {
+            continue
+        }
+        last = current;
+        CHANGED.println_formatted(&[&path.to_string_lossy()]);
+        deploy();
+    }
+    release_interrupts();
+    return ExitCode::SUCCESS
+}
+
+/// The latest modification time and the total size of the file or the files of the dir.
+fn signature(path: &Path) -> Option<(SystemTime, u64)> {
+    let files = match path.is_dir() {
+        true => fs::read_dir(path).ok()?
+            .filter_map(Result::ok)
+            .map(|it| it.path())
+            .collect(),
+        false => vec![path.to_path_buf()],
     };
-    let pattern = Regex::new(r"^\d+\.\d+\.\d+$").unwrap();
-    return fs::read_dir(&path)
-        .string_err()?
+    return files.iter()
+        .filter_map(|it| fs::metadata(it).ok())
+        .filter_map(|it| Some((it.modified().ok()?, it.len())))
+        .reduce(|(time, size), (other_time, other_size)| (time.max(other_time), size + other_size))
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64())
+}
+
+/// A split dir, a bundle or an .aab turned into the APK files, the extracted ones go to `tmp`.
+fn collect_apks(path: &Path, tmp: &Path, config: &Config) -> Result<Vec<PathBuf>, String> {
+    let ext = path.extension()
+        .and_then(|it| it.to_str())
+        .map(str::to_lowercase);
+    let apks = match ext.as_deref() {
+        _ if path.is_dir() => apks_in(path)?,
+        Some(AAB) => extract_apks(&build_apks(path, tmp, config)?, tmp)?,
+        Some(ext) if BUNDLES.contains(&ext) => extract_apks(path, tmp)?,
+        _ => vec![path.to_path_buf()],
+    };
+    return match apks.is_empty() {
+        true => Err(NO_APKS.value().to_string()),
+        false => Ok(apks),
+    }
+}
+
+pub fn apks_in(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut apks = fs::read_dir(dir).string_err()?
         .filter_map(Result::ok)
-        .filter(|it| it.metadata().map(|m| m.is_dir()).unwrap_or(false) && pattern.is_match(&it.file_name().to_string_lossy()))
         .map(|it| it.path())
-        .max()
-        .map(|it| it.join("aapt"))
-        .ok_or_else(|| NO_BUILD_TOOLS.formatted(&[&path.to_string()]));
-}
-
-fn install(device: &AdbDevice, apk: &String) -> Output {
-    let args = AdbArgs::spawn(&[INSTALL, apk.as_str()]);
-    return run_adb_with(&device, args);
-}
-
-fn get_package_activity(aapt: PathBuf, apk: &String) -> (String, String) {
-    let text = Command::new(aapt)
-        .arg("d").arg("xmltree").arg(apk).arg("AndroidManifest.xml")
-        .output().unwrap()
-        .stdout()
-        .replace('\n', " ");
-    let package = Regex::new(r#" A: package="[^"]+"#).unwrap();
-    let package = package.find(&text).unwrap().as_str();
-    let offset = package.index_of('"').unwrap() + 1;
-    let package = package[offset..].to_string();
-    let activity = Regex::new(r#" E: activity.+="android\.intent\.action\.MAIN""#).unwrap();
-    let activity = activity.find(&text).unwrap().as_str();
-    let name = Regex::new(r#" A: android:name\(0x\d{8}\)="[^"]+"#).unwrap();
-    let activity = name.find(activity).unwrap().as_str();
-    let offset = activity.index_of('"').unwrap() + 1;
-    let activity = activity[offset..].to_string();
-    return (package, activity);
-}
-
-fn launch(device: &AdbDevice, package: String, activity: String) -> Output {
-    let command = format!("am start -a android.intent.action.MAIN -n {package}/{activity}");
-    let args = AdbArgs::spawn(&[SHELL, command.as_str()]);
-    return run_adb_with(&device, args);
+        .filter(|it| it.is_file() && it.extension().map(|it| it == APK).unwrap_or(false))
+        .collect::<Vec<_>>();
+    apks.sort();
+    return Ok(apks)
+}
+
+/// Runs `bundletool build-apks`, the result has the splits for every device configuration.
+fn build_apks(aab: &Path, tmp: &Path, config: &Config) -> Result<PathBuf, String> {
+    let bundletool = config.bundletool()
+        .ok_or_else(|| NO_BUNDLETOOL.formatted(&[&config_path().to_string()]))?;
+    let java = which::which(JAVA)
+        .ok()
+        .or_else(|| env::var_os(JAVA_HOME).map(|it| PathBuf::from(it).join("bin").join(JAVA)))
+        .ok_or_else(|| NO_JAVA.value().to_string())?;
+    let output = tmp.join(format!("{}.{APKS}", aab.file_stem().unwrap_or_default().to_string_lossy()));
+    BUILDING_APKS.println_formatted(&[&aab.to_string_lossy()]);
+    let result = Command::new(java)
+        .arg("-jar").arg(bundletool)
+        .arg("build-apks")
+        .arg(format!("--bundle={}", aab.to_string_lossy()))
+        .arg(format!("--output={}", output.to_string()))
+        .output()
+        .string_err()?;
+    return match result.status.success() {
+        true => Ok(output),
+        false => Err(result.stderr()),
+    }
+}
+
+/// Unpacks the APKs of a bundle flat into the dir.
+fn extract_apks(bundle: &Path, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut zip = ZipArchive::new(File::open(bundle).string_err()?).string_err()?;
+    let mut apks = vec![];
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).string_err()?;
+        let path = match entry.enclosed_name() {
+            Some(path) if entry.is_file() && !path.starts_with(STANDALONES) => path,
+            _ => continue,
+        };
+        // the name comes from the archive, take the file name only
+        let name = match path.file_name().map(PathBuf::from) {
+            Some(name) if name.extension().map(|it| it == APK).unwrap_or(false) => name,
+            _ => continue,
+        };
+        let path = dir.join(name);
+        io::copy(&mut entry, &mut File::create(&path).string_err()?).string_err()?;
+        apks.push(path);
+    }
+    return Ok(apks)
+}
+
+/// Returns the APKs with their manifests and the manifest of the base one.
+fn read_manifests(apks: Vec<PathBuf>) -> Result<(Vec<(PathBuf, Manifest)>, Manifest), String> {
+    let apks = apks.into_iter()
+        .map(|apk| Manifest::from_apk(&apk).map(|it| (apk, it)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let base = apks.iter()
+        .map(|(_, it)| it)
+        .find(|it| it.split.is_none())
+        .cloned()
+        .ok_or_else(|| INVALID_MANIFEST.value().to_string())?;
+    return Ok((apks, base))
+}
+
+/// Explains the failures and retries with the fixes the user agrees to, each at most once.
+pub fn install_with_fixes(device: &AdbDevice, apks: &[PathBuf], package: &str, flags: &[&str]) -> Output {
+    let mut flags = flags.to_vec();
+    let mut uninstalled = false;
+    loop {
+        let output = install(device, apks, &flags);
+        if output.status.success() {
+            return output
+        }
+        let error = match InstallError::parse(&format!("{}\n{}", output.stdout(), output.stderr())) {
+            Some(error) => error,
+            None => return output,
+        };
+        if let Some(explanation) = error.explanation() {
+            format!("{}: {}", error.code(), explanation.value()).eprintln();
+        }
+        if error == InstallError::InsufficientStorage {
+            let df = run_adb_with(device, AdbArgs::run(&[SHELL, FREE_SPACE_COMMAND])).stdout();
+            // Filesystem Size Used Avail Use% Mounted on
+            if let Some(free) = df.lines().last().and_then(|it| it.split_whitespace().nth(3)) {
+                FREE_SPACE.println_formatted(&[free]);
+            }
+        }
+        let applied = match error.fix() {
+            Some((InstallFix::Uninstall, question)) if !uninstalled && interactive_confirm(question.value()) => {
+                let command = format!("pm uninstall {package}");
+                uninstalled = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()])).status.success();
+                uninstalled
+            },
+            Some((InstallFix::Flag(flag), question)) if !flags.contains(&flag) && interactive_confirm(question.value()) => {
+                flags.push(flag);
+                true
+            },
+            _ => false,
+        };
+        if !applied {
+            return output
+        }
+    }
+}
+
+fn install(device: &AdbDevice, apks: &[PathBuf], flags: &[&str]) -> Output {
+    let command = match apks.len() {
+        1 => INSTALL,
+        _ => INSTALL_MULTIPLE,
+    };
+    let mut args = vec![command.to_string()];
+    args.extend(flags.iter().map(|it| it.to_string()));
+    args.extend(apks.iter().map(|it| it.to_string()));
+    let child = adb_args_with(device, AdbArgs::run(&args))
+        .command()
+        .and_then(|mut it| it.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().string_err());
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => return Output { status: error_exit_status(), stdout: vec![], stderr: e.into_bytes() },
+    };
+    let stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || tee(stderr, io::stderr()));
+    let stdout = tee(child.stdout.take().unwrap(), io::stdout());
+    let status = child.wait().unwrap_or_else(|_| error_exit_status());
+    return Output { status, stdout, stderr: stderr.join().unwrap() }
+}
+
+/// Shows the output of adb as it comes, the progress included, and keeps it for `InstallError::parse`.
+fn tee(mut src: impl Read, mut dst: impl Write) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut buf = [0u8; 4096];
+    while let Ok(count) = src.read(&mut buf) {
+        if count == 0 {
+            break
+        }
+        let _ = dst.write_all(&buf[..count]).and_then(|_| dst.flush());
+        bytes.extend_from_slice(&buf[..count]);
+    }
+    return bytes
 }
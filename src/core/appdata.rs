@@ -0,0 +1,126 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::destination::Destination;
+use crate::core::ext::{OutputExt, PrintExt, ResultExt};
+use crate::core::r#const::{EXEC_OUT, PUSH, SHELL};
+use crate::core::selector::{adb_args_with, resolve_device, run_adb_with};
+use crate::core::strings::{DATA_RESTORED, NOT_DEBUGGABLE, SAVED, USAGE};
+use crate::core::util::{ensure_parent_exists, format_file_name};
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{ExitCode, Stdio};
+
+const BACKUP: &str = "backup";
+const RESTORE: &str = "restore";
+const ARCHIVE_NAME: &str = "_%Y%m%d-%H%M%S.tar";
+const DEVICE_TMP: &str = "/data/local/tmp";
+const USAGE_TEXT: &str = "appdata backup app.package.name [destination] | appdata restore app.package.name archive.tar";
+const AM_FORCE_STOP: &str = "am force-stop";
+
+/// The `appdata backup|restore` command.
+pub fn appdata(args: &[String]) -> ExitCode {
+    let action = args.first().map(String::as_str).unwrap_or_default();
+    let (package, path) = match (action, args.get(1), args.get(2)) {
+        (BACKUP, Some(package), dst) => (package, dst.cloned().unwrap_or_default()),
+        (RESTORE, Some(package), Some(archive)) => (package, archive.clone()),
+        _ => {
+            USAGE.formatted(&[USAGE_TEXT]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    if let Err(code) = check_debuggable(&device, package) {
+        return code
+    }
+    return match action {
+        BACKUP => backup(&device, package, path.dst()),
+        _ => restore(&device, package, &path.dst()),
+    }
+}
+
+/// run-as works only for the debuggable apps.
+pub fn check_debuggable(device: &AdbDevice, package: &str) -> Result<(), ExitCode> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, &run_as(package, "true")]));
+    if !output.status.success() {
+        NOT_DEBUGGABLE.formatted(&[package]).eprintln();
+        output.print_out_and_err();
+        return Err(ExitCode::FAILURE)
+    }
+    return Ok(())
+}
+
+fn backup(device: &AdbDevice, package: &str, dst: PathBuf) -> ExitCode {
+    let dst = dst.join(format_file_name(&format!("{package}{ARCHIVE_NAME}")));
+    ensure_parent_exists(&dst);
+    let code = backup_to(device, package, &dst);
+    if code == ExitCode::SUCCESS {
+        SAVED.println_formatted(&[&dst.to_string_lossy()]);
+    }
+    return code
+}
+
+/// The archive keeps the paths relative to /data, so the credential and device encrypted storages get into one file.
+pub fn backup_to(device: &AdbDevice, package: &str, archive: &Path) -> ExitCode {
+    let dirs = data_dirs(package);
+    let tar = format!(
+        "cd /data && tar -cf - --exclude={0}/cache --exclude={0}/code_cache $(ls -d {1} 2>/dev/null) 2>/dev/null",
+        dirs[0], dirs.join(" "),
+    );
+    // the archive goes straight into the file, the data can be larger than the memory
+    let output = File::create(archive).string_err().and_then(|file| {
+        adb_args_with(device, AdbArgs::run(&[EXEC_OUT, &run_as(package, &tar)]))
+            .command()?
+            .stdout(file)
+            .stderr(Stdio::piped())
+            .output()
+            .string_err()
+    });
+    let written = fs::metadata(archive).map(|it| it.len() > 0).unwrap_or(false);
+    match output {
+        Ok(output) if output.status.success() && written => return ExitCode::SUCCESS,
+        Ok(output) => output.print_err(),
+        Err(e) => e.eprintln(),
+    }
+    let _ = fs::remove_file(archive);
+    return ExitCode::FAILURE
+}
+
+/// The app is stopped before the extraction to not overwrite the restored files,
+/// its data is removed first, so no files of the current state are left next to the restored ones.
+/// Extracting as the app keeps the files owned by its uid and labeled with its SELinux context.
+pub fn restore(device: &AdbDevice, package: &str, archive: &Path) -> ExitCode {
+    let tmp = format!("{DEVICE_TMP}/{package}.tar");
+    let output = run_adb_with(device, AdbArgs::run(&[PUSH, &archive.to_string_lossy(), &tmp]));
+    if !output.status.success() {
+        output.print_out_and_err();
+        return output.exit_code()
+    }
+    let stop = format!("{AM_FORCE_STOP} {package}");
+    // the lib link belongs to the system
+    let clear = format!(
+        "cd /data && for dir in {}; do if [ -d $dir ]; then find $dir -mindepth 1 -maxdepth 1 ! -name lib -exec rm -rf {{}} + || exit 1; fi; done",
+        data_dirs(package).join(" "),
+    );
+    let clear = run_as(package, &clear);
+    let extract = format!("cat {tmp} | {}", run_as(package, "cd /data && tar -xof -"));
+    let command = format!("{stop} && {clear} && {extract}; code=$?; rm -f {tmp}; exit $code");
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, &command]));
+    if !output.status.success() {
+        output.print_out_and_err();
+        return output.exit_code()
+    }
+    DATA_RESTORED.println_formatted(&[package]);
+    return ExitCode::SUCCESS
+}
+
+fn data_dirs(package: &str) -> [String; 2] {
+    [format!("user/0/{package}"), format!("user_de/0/{package}")]
+}
+
+fn run_as(package: &str, command: &str) -> String {
+    format!("run-as {package} sh -c {}", shell_words::quote(command))
+}
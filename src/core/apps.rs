@@ -0,0 +1,139 @@
+use crate::core::adb_device::AdbDevice;
+use crate::core::apks::{apks_in, get_version, install_with_fixes, package_paths, pull};
+use crate::core::destination::Destination;
+use crate::core::ext::{OutputExt, PrintExt, ResultExt};
+use crate::core::packages::{list_packages, PackageFilter};
+use crate::core::perms::{apply, permissions};
+use crate::core::selector::resolve_device;
+use crate::core::strings::{APPS_EXPORTED, APPS_IMPORTED, APP_SKIPPED, SAVED, USAGE};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::process::ExitCode;
+
+const EXPORT: &str = "export";
+const IMPORT: &str = "import";
+const USAGE_TEXT: &str = "apps export [destination] | apps import directory";
+const APPS_JSON: &str = "apps.json";
+
+/// An entry of apps.json, the APKs are in the dir named after the package.
+/// The installer is informational, the apps are installed by adb anyway.
+#[derive(Serialize, Deserialize)]
+pub struct ExportedApp {
+    pub package: String,
+    pub version_code: Option<u64>,
+    pub version_name: Option<String>,
+    pub installer: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// The `apps export|import` command.
+pub fn apps(args: &[String]) -> ExitCode {
+    let dir = args.get(1).cloned().unwrap_or_default().dst();
+    let import = match (args.first().map(String::as_str), args.get(1)) {
+        (Some(EXPORT), _) => false,
+        (Some(IMPORT), Some(_)) => true,
+        _ => {
+            USAGE.formatted(&[USAGE_TEXT]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let result = match import {
+        false => export(&device, &dir),
+        true => import_apps(&device, &dir),
+    };
+    return match result {
+        Ok(code) => code,
+        Err(e) => {
+            e.eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Pulls every third-party app the way `steal` does and writes apps.json.
+fn export(device: &AdbDevice, dir: &Path) -> Result<ExitCode, String> {
+    let packages = match list_packages(device, &PackageFilter::third_party()) {
+        Ok(packages) => packages,
+        Err(output) => {
+            output.print_err();
+            return Ok(output.exit_code())
+        }
+    };
+    let mut apps = vec![];
+    for (index, package) in packages.iter().enumerate() {
+        format!("[{}/{}] {}", index + 1, packages.len(), package.name).println();
+        let paths = match package_paths(device, &package.name) {
+            Ok(paths) => paths,
+            Err(_) => continue,
+        };
+        let app_dir = dir.join(&package.name);
+        let existed = app_dir.exists();
+        fs::create_dir_all(&app_dir).string_err()?;
+        if pull(device, &paths, &app_dir).is_err() {
+            // the import would take a left dir for an app
+            if !existed {
+                let _ = fs::remove_dir_all(&app_dir);
+            }
+            continue
+        }
+        let version = get_version(device, &package.name);
+        let granted = permissions(device, &package.name)
+            .into_iter()
+            .filter(|it| it.runtime && it.granted)
+            .map(|it| it.name)
+            .collect();
+        apps.push(ExportedApp {
+            package: package.name.clone(),
+            version_code: version.code.and_then(|it| it.parse().ok()),
+            version_name: version.name,
+            installer: version.installer,
+            permissions: granted,
+        });
+    }
+    let json = dir.join(APPS_JSON);
+    serde_json::to_writer_pretty(File::create(&json).string_err()?, &apps).string_err()?;
+    APPS_EXPORTED.println_formatted(&[&apps.len().to_string(), &packages.len().to_string()]);
+    SAVED.println_formatted(&[&json.to_string_lossy()]);
+    return match apps.len() < packages.len() {
+        true => Ok(ExitCode::FAILURE),
+        false => Ok(ExitCode::SUCCESS),
+    }
+}
+
+/// Installs the apps of apps.json the way `run` does, the ones installed at the same or a higher version are skipped.
+fn import_apps(device: &AdbDevice, dir: &Path) -> Result<ExitCode, String> {
+    let apps: Vec<ExportedApp> = serde_json::from_reader(File::open(dir.join(APPS_JSON)).string_err()?).string_err()?;
+    let (mut installed, mut skipped, mut failed) = (0, 0, 0);
+    for (index, app) in apps.iter().enumerate() {
+        format!("[{}/{}] {}", index + 1, apps.len(), app.package).println();
+        let current = get_version(device, &app.package).code.and_then(|it| it.parse::<u64>().ok());
+        if let (Some(current), Some(exported)) = (current, app.version_code) {
+            if current >= exported {
+                APP_SKIPPED.println_formatted(&[&current.to_string()]);
+                skipped += 1;
+                continue
+            }
+        }
+        let apks = apks_in(&dir.join(&app.package)).unwrap_or_default();
+        if apks.is_empty() || !install_with_fixes(device, &apks, &app.package, &[]).status.success() {
+            failed += 1;
+            continue
+        }
+        if !app.permissions.is_empty() {
+            apply(device, &app.package, app.permissions.clone(), true);
+        }
+        installed += 1;
+    }
+    APPS_IMPORTED.println_formatted(&[&installed.to_string(), &skipped.to_string(), &failed.to_string()]);
+    return match failed {
+        0 => Ok(ExitCode::SUCCESS),
+        _ => Ok(ExitCode::FAILURE),
+    }
+}
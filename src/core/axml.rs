@@ -0,0 +1,303 @@
+use crate::core::ext::ResultExt;
+use crate::core::strings::INVALID_MANIFEST;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const ANDROID_MANIFEST: &str = "AndroidManifest.xml";
+const RES_XML: u16 = 0x0003;
+const RES_STRING_POOL: u16 = 0x0001;
+const RES_XML_RESOURCE_MAP: u16 = 0x0180;
+const RES_XML_START_ELEMENT: u16 = 0x0102;
+const RES_XML_END_ELEMENT: u16 = 0x0103;
+const UTF8_FLAG: u32 = 1 << 8;
+const NO_INDEX: u32 = 0xFFFF_FFFF;
+const TYPE_REFERENCE: u8 = 0x01;
+const TYPE_STRING: u8 = 0x03;
+const TYPE_INT_DEC: u8 = 0x10;
+const TYPE_INT_HEX: u8 = 0x11;
+const TYPE_BOOLEAN: u8 = 0x12;
+
+const ATTR_NAME: (&str, u32) = ("name", 0x0101_0003);
+const ATTR_ENABLED: (&str, u32) = ("enabled", 0x0101_000e);
+const ATTR_VERSION_CODE: (&str, u32) = ("versionCode", 0x0101_021b);
+const ATTR_VERSION_NAME: (&str, u32) = ("versionName", 0x0101_021c);
+const ATTR_MIN_SDK: (&str, u32) = ("minSdkVersion", 0x0101_020c);
+const ATTR_TARGET_SDK: (&str, u32) = ("targetSdkVersion", 0x0101_0270);
+const ATTR_COMPILE_SDK: (&str, u32) = ("compileSdkVersion", 0x0101_0572);
+// the manifest attributes out of the android namespace, they have no resource ids
+const ATTR_PACKAGE: (&str, u32) = ("package", 0);
+const ATTR_SPLIT: (&str, u32) = ("split", 0);
+// written by aapt2 before compileSdkVersion existed
+const ATTR_PLATFORM_BUILD: (&str, u32) = ("platformBuildVersionCode", 0);
+const ACTION_MAIN: &str = "android.intent.action.MAIN";
+const LAUNCHER_CATEGORIES: &[&str] = &["android.intent.category.LAUNCHER", "android.intent.category.LEANBACK_LAUNCHER"];
+
+/// What `run` and `apkinfo` need from AndroidManifest.xml of an APK.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Manifest {
+    pub package: String,
+    /// the name of a config or feature split, none for the base APK
+    pub split: Option<String>,
+    pub version_code: Option<u64>,
+    pub version_name: Option<String>,
+    pub min_sdk: Option<u32>,
+    pub target_sdk: Option<u32>,
+    pub compile_sdk: Option<u32>,
+    pub permissions: Vec<String>,
+    /// the enabled activities and activity-aliases with MAIN and LAUNCHER, the first one is launched
+    pub launchers: Vec<String>,
+}
+
+#[derive(Debug)]
+enum Value {
+    String(String),
+    Int(u32),
+    Bool(bool),
+    Reference(u32),
+}
+
+impl Value {
+    fn string(&self) -> String {
+        match self {
+            Value::String(value) => value.clone(),
+            Value::Int(value) => value.to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Reference(id) => format!("@0x{id:08x}"),
+        }
+    }
+
+    fn int(&self) -> Option<u32> {
+        match self {
+            Value::Int(value) => Some(*value),
+            Value::String(value) => value.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+struct Attribute {
+    name: String,
+    id: Option<u32>,
+    value: Value,
+}
+
+enum Event {
+    Start(String, Vec<Attribute>),
+    End,
+}
+
+impl Manifest {
+    pub fn from_apk(apk: &Path) -> Result<Manifest, String> {
+        let file = File::open(apk).string_err()?;
+        let mut zip = zip::ZipArchive::new(file).string_err()?;
+        let mut entry = zip.by_name(ANDROID_MANIFEST).string_err()?;
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes).string_err()?;
+        return Manifest::parse(&bytes)
+    }
+
+    pub fn launcher(&self) -> Option<String> {
+        self.launchers.first().cloned()
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Manifest, String> {
+        let events = parse_events(bytes).ok_or_else(|| INVALID_MANIFEST.value().to_string())?;
+        let mut manifest = Manifest::default();
+        let mut path: Vec<String> = vec![];
+        // name and whether it's a launcher of the current activity
+        let mut activity: Option<(String, bool)> = None;
+        let mut action_main = false;
+        let mut launcher_category = false;
+        for event in events {
+            let (name, attrs) = match event {
+                Event::Start(name, attrs) => (name, attrs),
+                Event::End => {
+                    match path.pop().as_deref() {
+                        Some("intent-filter") if action_main && launcher_category => {
+                            if let Some((_, launcher)) = activity.as_mut() {
+                                *launcher = true;
+                            }
+                        },
+                        Some("activity" | "activity-alias") => match activity.take() {
+                            Some((name, true)) if !manifest.launchers.contains(&name) => manifest.launchers.push(name),
+                            _ => {},
+                        },
+                        _ => {},
+                    }
+                    continue
+                },
+            };
+            let value = |key: (&str, u32)| attrs.iter()
+                .find(|it| it.id.map(|id| id == key.1).unwrap_or(it.name == key.0))
+                .map(|it| &it.value);
+            match (path.last().map(String::as_str), name.as_str()) {
+                (None, "manifest") => {
+                    manifest.package = value(ATTR_PACKAGE).map(Value::string).unwrap_or_default();
+                    manifest.split = value(ATTR_SPLIT).map(Value::string);
+                    manifest.version_code = value(ATTR_VERSION_CODE).and_then(Value::int).map(u64::from);
+                    manifest.version_name = value(ATTR_VERSION_NAME).map(Value::string);
+                    manifest.compile_sdk = value(ATTR_COMPILE_SDK)
+                        .or(value(ATTR_PLATFORM_BUILD))
+                        .and_then(Value::int);
+                },
+                (Some("manifest"), "uses-sdk") => {
+                    manifest.min_sdk = value(ATTR_MIN_SDK).and_then(Value::int);
+                    manifest.target_sdk = value(ATTR_TARGET_SDK).and_then(Value::int);
+                },
+                (Some("manifest"), "uses-permission" | "uses-permission-sdk-23") => {
+                    if let Some(permission) = value(ATTR_NAME) {
+                        manifest.permissions.push(permission.string());
+                    }
+                },
+                (Some("application"), "activity" | "activity-alias") => {
+                    let enabled = !matches!(value(ATTR_ENABLED), Some(Value::Bool(false)));
+                    activity = value(ATTR_NAME)
+                        .filter(|_| enabled)
+                        .map(|it| (class_name(&manifest.package, &it.string()), false));
+                },
+                (Some("activity" | "activity-alias"), "intent-filter") => {
+                    action_main = false;
+                    launcher_category = false;
+                },
+                (Some("intent-filter"), "action") => {
+                    action_main |= value(ATTR_NAME).map(Value::string).as_deref() == Some(ACTION_MAIN);
+                },
+                (Some("intent-filter"), "category") => {
+                    let category = value(ATTR_NAME).map(Value::string).unwrap_or_default();
+                    launcher_category |= LAUNCHER_CATEGORIES.contains(&category.as_str());
+                },
+                _ => {},
+            }
+            path.push(name);
+        }
+        if manifest.package.is_empty() {
+            return Err(INVALID_MANIFEST.value().to_string())
+        }
+        return Ok(manifest)
+    }
+}
+
+/// Expands `.Main` and `Main` to the full class name.
+pub fn class_name(package: &str, name: &str) -> String {
+    match () {
+        _ if name.starts_with('.') => format!("{package}{name}"),
+        _ if !name.contains('.') => format!("{package}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+fn parse_events(bytes: &[u8]) -> Option<Vec<Event>> {
+    let mut events = vec![];
+    if u16_at(bytes, 0)? != RES_XML {
+        return None
+    }
+    let mut strings: Vec<String> = vec![];
+    let mut ids: Vec<u32> = vec![];
+    let mut offset = u16_at(bytes, 2)? as usize;
+    let end = (u32_at(bytes, 4)? as usize).min(bytes.len());
+    while offset + 8 <= end {
+        let chunk_type = u16_at(bytes, offset)?;
+        let header_size = u16_at(bytes, offset + 2)? as usize;
+        let size = u32_at(bytes, offset + 4)? as usize;
+        let chunk = bytes.get(offset..offset.checked_add(size)?)?;
+        match chunk_type {
+            RES_STRING_POOL => strings = parse_string_pool(chunk)?,
+            RES_XML_RESOURCE_MAP => ids = chunk.get(header_size..)?.chunks_exact(4)
+                .map(|it| u32::from_le_bytes([it[0], it[1], it[2], it[3]]))
+                .collect(),
+            RES_XML_START_ELEMENT => events.push(parse_element(chunk, header_size, &strings, &ids)?),
+            RES_XML_END_ELEMENT => events.push(Event::End),
+            _ => {},
+        }
+        if size == 0 {
+            return None
+        }
+        offset += size;
+    }
+    return Some(events)
+}
+
+fn parse_string_pool(chunk: &[u8]) -> Option<Vec<String>> {
+    let header_size = u16_at(chunk, 2)? as usize;
+    let count = u32_at(chunk, 8)? as usize;
+    let flags = u32_at(chunk, 16)?;
+    let strings_start = u32_at(chunk, 20)? as usize;
+    let mut strings = Vec::with_capacity(count.min(chunk.len() / 4));
+    for index in 0..count {
+        let offset = strings_start + u32_at(chunk, header_size + index * 4)? as usize;
+        let string = match flags & UTF8_FLAG != 0 {
+            true => {
+                let (_, offset) = utf8_length(chunk, offset)?;
+                let (length, offset) = utf8_length(chunk, offset)?;
+                String::from_utf8_lossy(chunk.get(offset..offset + length)?).into_owned()
+            },
+            false => {
+                let (length, offset) = utf16_length(chunk, offset)?;
+                let units = chunk.get(offset..offset + length * 2)?
+                    .chunks_exact(2)
+                    .map(|it| u16::from_le_bytes([it[0], it[1]]))
+                    .collect::<Vec<_>>();
+                String::from_utf16_lossy(&units)
+            },
+        };
+        strings.push(string);
+    }
+    return Some(strings)
+}
+
+fn parse_element(chunk: &[u8], header_size: usize, strings: &[String], ids: &[u32]) -> Option<Event> {
+    let string = |index: u32| strings.get(index as usize).cloned();
+    let name = string(u32_at(chunk, header_size + 4)?)?;
+    let attribute_start = u16_at(chunk, header_size + 8)? as usize;
+    let attribute_size = u16_at(chunk, header_size + 10)? as usize;
+    let attribute_count = u16_at(chunk, header_size + 12)? as usize;
+    let mut attrs = Vec::with_capacity(attribute_count);
+    for index in 0..attribute_count {
+        let offset = header_size + attribute_start + index * attribute_size;
+        let name_index = u32_at(chunk, offset + 4)?;
+        let raw = u32_at(chunk, offset + 8)?;
+        let data_type = *chunk.get(offset + 15)?;
+        let data = u32_at(chunk, offset + 16)?;
+        let value = match data_type {
+            _ if raw != NO_INDEX => Value::String(string(raw)?),
+            TYPE_STRING => Value::String(string(data)?),
+            TYPE_INT_DEC | TYPE_INT_HEX => Value::Int(data),
+            TYPE_BOOLEAN => Value::Bool(data != 0),
+            TYPE_REFERENCE => Value::Reference(data),
+            _ => Value::Int(data),
+        };
+        attrs.push(Attribute {
+            name: string(name_index).unwrap_or_default(),
+            id: ids.get(name_index as usize).copied(),
+            value,
+        });
+    }
+    return Some(Event::Start(name, attrs))
+}
+
+fn utf8_length(bytes: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = *bytes.get(offset)? as usize;
+    return match first & 0x80 != 0 {
+        true => Some((((first & 0x7F) << 8) | *bytes.get(offset + 1)? as usize, offset + 2)),
+        false => Some((first, offset + 1)),
+    }
+}
+
+fn utf16_length(bytes: &[u8], offset: usize) -> Option<(usize, usize)> {
+    let first = u16_at(bytes, offset)? as usize;
+    return match first & 0x8000 != 0 {
+        true => Some((((first & 0x7FFF) << 16) | u16_at(bytes, offset + 2)? as usize, offset + 4)),
+        false => Some((first, offset + 2)),
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes = bytes.get(offset..offset + 2)?;
+    return Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    return Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
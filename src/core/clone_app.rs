@@ -0,0 +1,90 @@
+use crate::core::apks::{apks_in, install_with_fixes, package_paths, pull};
+use crate::core::appdata::{backup_to, check_debuggable, restore};
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::flags::Flags;
+use crate::core::packages::{package_or_pick, PackageFilter};
+use crate::core::perms::{apply, permissions};
+use crate::core::selector::device_by;
+use crate::core::strings::APP_CLONED;
+use std::process::ExitCode;
+
+const FROM: &str = "from";
+const TO: &str = "to";
+const DATA: &str = "data";
+const PERMISSIONS: &str = "permissions";
+const DATA_ARCHIVE: &str = "data.tar";
+
+/// The `clone-app` command, the APKs and the data go through a temp dir.
+pub fn clone_app(args: &[String]) -> ExitCode {
+    let flags = match Flags::parse(args, &[DATA, PERMISSIONS], &[FROM, TO]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let from = match device_by(flags.value(FROM), None) {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let to = match device_by(flags.value(TO), Some(&from)) {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let package = match package_or_pick(&from, flags.arg(0), &PackageFilter::third_party()) {
+        Ok(package) => package,
+        Err(code) => return code,
+    };
+    let with_data = flags.has(DATA);
+    if with_data {
+        if let Err(code) = check_debuggable(&from, &package) {
+            return code
+        }
+    }
+    let paths = match package_paths(&from, &package) {
+        Ok(paths) => paths,
+        Err(code) => return code,
+    };
+    let tmp = match tempfile::tempdir() {
+        Ok(tmp) => tmp,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    if let Err(code) = pull(&from, &paths, tmp.path()) {
+        return code
+    }
+    let apks = match apks_in(tmp.path()) {
+        Ok(apks) => apks,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let output = install_with_fixes(&to, &apks, &package, &[]);
+    if !output.status.success() {
+        return output.exit_code()
+    }
+    if flags.has(PERMISSIONS) {
+        let granted = permissions(&from, &package)
+            .into_iter()
+            .filter(|it| it.runtime && it.granted)
+            .map(|it| it.name)
+            .collect::<Vec<_>>();
+        apply(&to, &package, granted, true);
+    }
+    if with_data {
+        let archive = tmp.path().join(DATA_ARCHIVE);
+        let code = backup_to(&from, &package, &archive);
+        if code != ExitCode::SUCCESS {
+            return code
+        }
+        let code = restore(&to, &package, &archive);
+        if code != ExitCode::SUCCESS {
+            return code
+        }
+    }
+    APP_CLONED.println_formatted(&[&package, &from.description, &to.description]);
+    return ExitCode::SUCCESS
+}
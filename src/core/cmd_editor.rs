@@ -1,4 +1,6 @@
 use crate::core::ext::try_make_colored;
+use crate::core::packages::cached_package_names;
+use crate::core::r#const::PACKAGE_COMMANDS;
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
@@ -70,7 +72,13 @@ impl Completer for CmdHelper {
 
         let prefix = &line[start..pos];
         let mut out = Vec::new();
-        for cmd in &self.cmds {
+        let command = line[..start].split_whitespace().next();
+        let candidates = match command {
+            Some(command) if PACKAGE_COMMANDS.contains(&command) => cached_package_names(),
+            Some(_) => vec![],
+            None => self.cmds.clone(),
+        };
+        for cmd in &candidates {
             if cmd.starts_with(prefix) {
                 out.push(Pair {
                     display: cmd.clone(),
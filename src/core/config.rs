@@ -1,26 +1,68 @@
 use crate::core::destination::Destination;
-use crate::core::ext::{OptionExt, PathBufExt, ResultExt, ResultToOption, Rslt, StrExt};
-use crate::core::r#const::{ADB, BUILD_TOOLS, PLATFORM_TOOLS};
+use crate::core::ext::{OptionExt, PathBufExt, PrintExt, ResultExt, ResultToOption, Rslt, StrExt};
+use crate::core::r#const::{ADB, PLATFORM_TOOLS};
 use crate::core::system::{adb_name, config_path, make_executable};
 use itertools::Itertools;
+use serde::Serializer;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
 use std::path::PathBuf;
+use std::time::Duration;
+use crate::core::hook::{HookEvent, HookSet};
+use crate::core::strings::UNKNOWN_PLACEHOLDER;
+use crate::core::template::unknown_placeholders;
+use crate::core::toggles::builtin_toggles;
 use crate::core::util::string;
 
 pub static mut ADB_PATH: Option<String> = None;
+const DEFAULT_HOOK_TIMEOUT: u64 = 60;
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_hook")]
-    hook: Option<String>,
+    hook: Option<Hooks>,
     #[serde(default)]
     pub environment: Environment,
     #[serde(default)]
     pub screenshots: Screenshots,
     #[serde(default)]
     pub screencasts: Screencasts,
+    #[serde(default)]
+    pub demo: Demo,
+    #[serde(default)]
+    pub logs: Logs,
+    #[serde(default)]
+    pub crashes: Crashes,
+    /// name: toggle, the built-in ones are added unless redefined and are not written back
+    #[serde(default = "builtin_toggles", serialize_with = "user_toggles", skip_serializing_if = "only_builtin")]
+    pub toggles: BTreeMap<String, Toggle>,
+    /// name: profile
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// serial: the values before the first `profile apply`, `profile restore` brings them back
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile_backups: BTreeMap<String, Profile>,
+    /// serial: alias
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+/// A hook path, a list of them or the lists for both events.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Hooks {
+    Single(String),
+    List(Vec<String>),
+    Events(HookEvents),
+}
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookEvents {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+    /// seconds
+    pub timeout: Option<u64>,
 }
 #[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Environment {
@@ -29,14 +71,19 @@ pub struct Environment {
     build_tools: Option<String>,
     #[serde(rename = "platform-tools")]
     platform_tools: Option<String>,
+    /// bundletool.jar, `run` builds the APKs of an .aab with it
+    bundletool: Option<String>,
 }
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Screenshots {
     pub name: String,
+    pub series_name: String,
     pub sources: Vec<String>,
     pub destination: String,
-    hook: Option<String>,
+    hook: Option<Hooks>,
+    /// mss and rec turn the SystemUI demo mode on for the capture
+    pub demo_mode: bool,
 }
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
@@ -44,12 +91,100 @@ pub struct Screencasts {
     pub name: String,
     pub sources: Vec<String>,
     pub destination: String,
-    hook: Option<String>,
+    hook: Option<Hooks>,
     pub show_taps: bool,
-    pub args: String,
+    pub stream: bool,
+    /// applied for the duration of a recording
+    pub profile: Option<String>,
+    #[serde(flatten)]
+    pub options: RecordOptions,
+    pub presets: BTreeMap<String, RecordOptions>,
+    pub convert: Convert,
+    #[serde(skip_serializing)]
+    args: Option<String>,
+}
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordOptions {
+    pub bit_rate: Option<String>,
+    pub size: Option<String>,
+    pub time_limit: Option<u32>,
+    pub rotate: Option<bool>,
+    pub bugreport: Option<bool>,
+    pub codec: Option<String>,
+}
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Convert {
+    pub format: Option<ConvertFormat>,
+    pub fps: u32,
+    pub max_width: u32,
+    pub keep_original: bool,
+}
+/// The status bar values of the SystemUI demo mode.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Demo {
+    /// hhmm
+    pub clock: String,
+    pub battery: u8,
+    pub plugged: bool,
+    /// 0..4, none hides the icon
+    pub wifi: Option<u8>,
+    /// 0..4, none hides the icon
+    pub mobile: Option<u8>,
+    pub mobile_type: Option<String>,
+    pub notifications: bool,
+}
+/// Where `log --save` writes the raw logcat.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Logs {
+    pub name: String,
+    pub destination: String,
+}
+/// Where `crashwatch` puts a folder for each crash or ANR.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Crashes {
+    pub name: String,
+    pub destination: String,
+    hook: Option<Hooks>,
+}
+/// setting: value, see `profile save`
+pub type Profile = BTreeMap<String, String>;
+/// A command with a fixed set of values, like `taps` or `animscale`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Toggle {
+    /// a shell command printing the current value
+    pub get: String,
+    /// value: shell command
+    pub set: BTreeMap<String, String>,
+    /// runs after each of the set commands
+    #[serde(default)]
+    pub post: Option<String>,
+    /// `deploy` makes a link with the toggle name
+    #[serde(default)]
+    pub symlink: bool,
+}
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConvertFormat {
+    Gif,
+    Webm,
 }
 
-fn default_hook() -> Option<String> { Some(string("~/Android/hook")) }
+fn default_hook() -> Option<Hooks> { Some(Hooks::Single(string("~/Android/hook"))) }
+
+fn user_toggles<S: Serializer>(toggles: &BTreeMap<String, Toggle>, serializer: S) -> Result<S::Ok, S::Error> {
+    let builtin = builtin_toggles();
+    serializer.collect_map(toggles.iter().filter(|(name, toggle)| builtin.get(*name) != Some(toggle)))
+}
+
+fn only_builtin(toggles: &BTreeMap<String, Toggle>) -> bool {
+    let builtin = builtin_toggles();
+    toggles.iter().all(|(name, toggle)| builtin.get(name) == Some(toggle))
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -58,6 +193,13 @@ impl Default for Config {
             environment: Environment::default(),
             screenshots: Screenshots::default(),
             screencasts: Screencasts::default(),
+            demo: Demo::default(),
+            logs: Logs::default(),
+            crashes: Crashes::default(),
+            toggles: builtin_toggles(),
+            profiles: BTreeMap::new(),
+            profile_backups: BTreeMap::new(),
+            aliases: BTreeMap::new(),
         }
     }
 }
@@ -66,12 +208,45 @@ impl Default for Screenshots {
     fn default() -> Self {
         Screenshots {
             name: string("Screenshot_%Y%m%d-%H%M%S.png"),
+            series_name: string("Screenshots_%Y%m%d-%H%M%S"),
             sources: vec![
                 string("/sdcard/Pictures/Screenshots"),
                 string("/sdcard/DCIM/Screenshots"),
             ],
             destination: string("~/Android/Screenshots"),
-            hook: Some(string("~/Android/Screenshots/hook")),
+            hook: Some(Hooks::Single(string("~/Android/Screenshots/hook"))),
+            demo_mode: false,
+        }
+    }
+}
+
+impl Default for Demo {
+    fn default() -> Self {
+        Demo {
+            clock: string("1200"),
+            battery: 100,
+            plugged: false,
+            wifi: Some(4),
+            mobile: Some(4),
+            mobile_type: None,
+            notifications: false,
+        }
+    }
+}
+impl Default for Logs {
+    fn default() -> Self {
+        Logs {
+            name: string("Log_%Y%m%d-%H%M%S.txt"),
+            destination: string("~/Android/Logs"),
+        }
+    }
+}
+impl Default for Crashes {
+    fn default() -> Self {
+        Crashes {
+            name: string("Crash_%Y%m%d-%H%M%S_{n}"),
+            destination: string("~/Android/Crashes"),
+            hook: Some(Hooks::Single(string("~/Android/Crashes/hook"))),
         }
     }
 }
@@ -85,9 +260,39 @@ impl Default for Screencasts {
                 string("/sdcard/Movies"),
             ],
             destination: string("~/Android/Screencasts"),
-            hook: Some(string("~/Android/Screencasts/hook")),
+            hook: Some(Hooks::Single(string("~/Android/Screencasts/hook"))),
             show_taps: true,
-            args: string("--bit-rate 5M"),
+            stream: true,
+            profile: None,
+            options: RecordOptions {
+                bit_rate: Some(string("5M")),
+                ..RecordOptions::default()
+            },
+            presets: BTreeMap::from([
+                (string("hq"), RecordOptions { bit_rate: Some(string("20M")), ..RecordOptions::default() }),
+                (string("small"), RecordOptions { bit_rate: Some(string("1M")), ..RecordOptions::default() }),
+            ]),
+            convert: Convert::default(),
+            args: None,
+        }
+    }
+}
+impl Default for Convert {
+    fn default() -> Self {
+        Convert {
+            format: None,
+            fps: 15,
+            max_width: 480,
+            keep_original: true,
+        }
+    }
+}
+
+impl ConvertFormat {
+    pub fn extension(&self) -> &str {
+        match self {
+            ConvertFormat::Gif => "gif",
+            ConvertFormat::Webm => "webm",
         }
     }
 }
@@ -97,8 +302,33 @@ impl Config {
         let config_path = config_path();
         let text = fs::read_to_string(&config_path)
             .unwrap_or_default();
-        return serde_yaml::from_str::<Config>(&text)
+        let mut config = serde_yaml::from_str::<Config>(&text)
             .unwrap_or_default();
+        config.screencasts.migrate_args();
+        for (name, toggle) in builtin_toggles() {
+            config.toggles.entry(name).or_insert(toggle);
+        }
+        config.check_placeholders();
+        return config
+    }
+
+    fn check_placeholders(&self) {
+        let templates = [
+            &self.screenshots.name,
+            &self.screenshots.series_name,
+            &self.screenshots.destination,
+            &self.screencasts.name,
+            &self.screencasts.destination,
+            &self.logs.name,
+            &self.logs.destination,
+            &self.crashes.name,
+            &self.crashes.destination,
+        ];
+        for template in templates {
+            for placeholder in unknown_placeholders(template) {
+                UNKNOWN_PLACEHOLDER.formatted(&[&placeholder, template]).eprintln();
+            }
+        }
     }
 
     pub fn write(&self) -> Rslt<()> {
@@ -133,14 +363,6 @@ impl Config {
         return unsafe { Option::clone(&*&raw const ADB_PATH) }
     }
 
-    pub fn build_tools(&self) -> Option<PathBuf> {
-        existing_or_none(
-            dir_checker,
-            self.environment.build_tools.clone().map(|it| it.dst()),
-            self.environment.sdk.clone().map(|it| it.dst().join(BUILD_TOOLS)),
-        )
-    }
-
     pub fn platform_tools(&self) -> Option<PathBuf> {
         existing_or_none(
             dir_checker,
@@ -149,20 +371,81 @@ impl Config {
         )
     }
 
-    pub fn screenshot_hook(&self) -> Option<PathBuf> {
+    pub fn bundletool(&self) -> Option<PathBuf> {
         existing_or_none(
             file_checker,
-            self.screenshots.hook.clone().map(|it| it.dst()),
-            self.hook.clone().map(|it| it.dst()),
-        ).and_then(|it| make_executable(it).to_option())
+            self.environment.bundletool.clone().map(|it| it.dst()),
+            None,
+        )
     }
 
-    pub fn screencast_hook(&self) -> Option<PathBuf> {
-        existing_or_none(
-            file_checker,
-            self.screencasts.hook.clone().map(|it| it.dst()),
-            self.hook.clone().map(|it| it.dst()),
-        ).and_then(|it| make_executable(it).to_option())
+    pub fn screenshot_hooks(&self) -> HookSet {
+        hook_set(&self.screenshots.hook, &self.hook)
+    }
+
+    pub fn screencast_hooks(&self) -> HookSet {
+        hook_set(&self.screencasts.hook, &self.hook)
+    }
+
+    pub fn crash_hooks(&self) -> HookSet {
+        hook_set(&self.crashes.hook, &self.hook)
+    }
+}
+
+impl Hooks {
+    fn paths(&self, event: HookEvent) -> Vec<String> {
+        match (self, event) {
+            (Hooks::Single(path), HookEvent::Post) => vec![path.clone()],
+            (Hooks::List(paths), HookEvent::Post) => paths.clone(),
+            (Hooks::Events(events), HookEvent::Pre) => events.pre.clone(),
+            (Hooks::Events(events), HookEvent::Post) => events.post.clone(),
+            _ => vec![],
+        }
+    }
+
+    fn timeout(&self) -> Option<u64> {
+        match self {
+            Hooks::Events(events) => events.timeout,
+            _ => None,
+        }
+    }
+}
+
+/// The section hooks take precedence over the common ones for each event.
+fn hook_set(section: &Option<Hooks>, common: &Option<Hooks>) -> HookSet {
+    let existing = |hooks: &Option<Hooks>, event: HookEvent| hooks.iter()
+        .flat_map(|it| it.paths(event))
+        .map(|it| it.dst())
+        .filter(file_checker)
+        .filter_map(|it| make_executable(it).to_option())
+        .collect::<Vec<PathBuf>>();
+    let events = [HookEvent::Pre, HookEvent::Post].map(|event| {
+        match existing(section, event) {
+            paths if paths.is_empty() => existing(common, event),
+            paths => paths,
+        }
+    });
+    let [pre, post] = events;
+    let timeout = section.as_ref().and_then(Hooks::timeout)
+        .or(common.as_ref().and_then(Hooks::timeout))
+        .unwrap_or(DEFAULT_HOOK_TIMEOUT);
+    return HookSet { pre, post, timeout: Duration::from_secs(timeout) }
+}
+
+impl Screencasts {
+    /// Moves the legacy free-form `args` into the typed options.
+    fn migrate_args(&mut self) {
+        let args = match self.args.take() {
+            Some(args) if !args.trim().is_empty() => args,
+            _ => return,
+        };
+        let args = args.split_whitespace()
+            .map(string)
+            .collect::<Vec<String>>();
+        match RecordOptions::parse(&args) {
+            Ok(options) => self.options = self.options.merge(&options),
+            Err(e) => e.eprintln(),
+        }
     }
 }
 
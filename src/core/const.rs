@@ -10,6 +10,18 @@ pub const REC: &str = "rec";
 pub const RECORD: &str = "record";
 pub const RUN: &str = "run";
 pub const STEAL: &str = "steal";
+pub const LAUNCH: &str = "launch";
+pub const PACKAGES: &str = "packages";
+pub const UNINSTALL: &str = "uninstall";
+pub const FORCE_STOP: &str = "force-stop";
+pub const KILL: &str = "kill";
+pub const STOP: &str = "stop";
+pub const RESTART: &str = "restart";
+pub const APPDATA: &str = "appdata";
+pub const PERMS: &str = "perms";
+pub const APKINFO: &str = "apkinfo";
+pub const CLONE_APP: &str = "clone-app";
+pub const APPS: &str = "apps";
 pub const PORT: &str = "port";
 pub const LAND: &str = "land";
 pub const FPORT: &str = "fport";
@@ -20,10 +32,14 @@ pub const BOUNDS: &str = "bounds";
 pub const TAPS: &str = "taps";
 pub const POINTER: &str = "pointer";
 pub const ANI_SCALE: &str = "animscale";
+pub const DEMO: &str = "demo";
 
 pub const SHELL: &str = "shell";
 pub const PULL: &str = "pull";
+pub const PUSH: &str = "push";
+pub const EXEC_OUT: &str = "exec-out";
 pub const INSTALL: &str = "install";
+pub const INSTALL_MULTIPLE: &str = "install-multiple";
 
 pub const CLEAR: &str = "clear";
 pub const EXIT: &str = "exit";
@@ -32,26 +48,41 @@ pub const DEPLOY: &str = "deploy";
 pub const UPDATE: &str = "update";
 pub const SDK: &str = "sdk";
 pub const FIX: &str = "fix";
+pub const LOG: &str = "log";
+pub const CRASHWATCH: &str = "crashwatch";
+pub const PROFILE: &str = "profile";
 
 pub const HELP_TEXT: &[&str] = &[
-    "lss [count]", "mss|shot [destination]", "lsc [count]", "msc|rec|record [destination]",
-    "bounds", "taps", "pointer", "animscale [scale]", "[f]port|[f]land|[no]accel", "sdk [path]",
-    "run app.apk", "steal app.package.name", "adb-ext update"
+    "lss [count]", "mss|shot [destination] [--burst N] [--every 500ms] [--for 5m]", "lsc [count]", "msc|rec|record [preset] [destination] [--gif|--webm] [--logcat[=app.package.name]] [--profile name]",
+    "bounds|taps|pointer|animscale [value]", "profile save|apply name", "profile restore", "demo [on|off]", "[f]port|[f]land|[no]accel", "sdk [path]",
+    "run app.apk|app.apks|app.apkm|app.xapk|app.aab|splits/ [--activity name] [--deeplink uri] [--extra key:type=value] [--debug] [--wait] [--grant-all] [--watch]",
+    "packages [name] [--third-party] [--system] [--disabled] [--debuggable]",
+    "kill|clear|restart|stop|force-stop|uninstall [app.package.name]",
+    "perms [app.package.name] [grant|revoke [permission…]|reset]", "apkinfo app.apk|app.package.name",
+    "clone-app [app.package.name] [--from device] [--to device] [--data] [--permissions]",
+    "apps export [destination]", "apps import directory",
+    "log [app.package.name] [--tag tag] [--level V|D|I|W|E|F] [--grep regex] [--save]", "crashwatch [app.package.name]",
+    "appdata backup app.package.name [destination]", "appdata restore app.package.name archive.tar",
+    "launch [app.package.name] [--activity name] [--deeplink uri] [--extra key:type=value] [--debug] [--wait]", "steal [app.package.name] [destination] [--bundle apks|apkm]", "adb-ext update"
 ];
+/// the commands completed with package names in the REPL
+pub const PACKAGE_COMMANDS: &[&str] = &[STEAL, LAUNCH, UNINSTALL, CLEAR, FORCE_STOP, KILL, STOP, RESTART, PERMS, APKINFO, CLONE_APP, LOG, CRASHWATCH];
+/// the commands which change the installed packages, the REPL completion lists them again after these
+pub const PACKAGE_CHANGING_COMMANDS: &[&str] = &[RUN, UNINSTALL, APPS, CLONE_APP, INSTALL, INSTALL_MULTIPLE];
 // todo parse adb help
 pub const SUGGESTIONS: &[&str] = &[
-    "devices", SHELL, PULL, "push",
+    "devices", SHELL, PULL, PUSH,
     "tcpip", "pair", "connect", "disconnect", "reconnect", "attach", "detach",
     "start-server", "kill-server", "connect", "disconnect", "reconnect",
-    INSTALL, "install-multiple", "install-multi-package", "uninstall", RUN, STEAL,
+    INSTALL, INSTALL_MULTIPLE, "install-multi-package", UNINSTALL, RUN, STEAL, LAUNCH, PACKAGES, FORCE_STOP,
+    KILL, STOP, RESTART, APPDATA, PERMS, APKINFO, CLONE_APP, APPS,
     LSS, LSC, MSS, SHOT, MSC, REC, RECORD,
     PORT, LAND, FPORT, FLAND, ACCEL, NO_ACCEL,
-    BOUNDS, TAPS, POINTER, ANI_SCALE, "logcat",
+    DEMO, PROFILE, LOG, CRASHWATCH, "logcat",
     CLEAR, EXIT, QUIT,
 ];
 
 pub const ADB: &str = "adb";
-pub const BUILD_TOOLS: &str = "build-tools";
 pub const PLATFORM_TOOLS: &str = "platform-tools";
 
 pub const ERROR_CODE: i32 = 1;
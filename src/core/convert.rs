@@ -0,0 +1,37 @@
+use crate::core::config::{Convert, ConvertFormat};
+use crate::core::ext::{OutputExt, PathBufExt};
+use crate::core::strings::{CONVERTING, NO_FFMPEG};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const FFMPEG: &str = "ffmpeg";
+
+pub fn convert_screencast(src: &PathBuf, format: ConvertFormat, options: &Convert) -> Result<PathBuf, String> {
+    let ffmpeg = which::which(FFMPEG).map_err(|_| NO_FFMPEG.value().to_string())?;
+    let dst = src.with_extension(format.extension());
+    CONVERTING.println_formatted(&[&dst.to_string()]);
+    let scale = format!("fps={},scale='min({},iw)':-2:flags=lanczos", options.fps, options.max_width);
+    let mut command = Command::new(ffmpeg);
+    command.args(["-y", "-v", "error", "-i"]).arg(src);
+    match format {
+        ConvertFormat::Gif => {
+            let filter = format!("{scale},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle");
+            command.arg("-filter_complex").arg(filter).args(["-loop", "0"]);
+        },
+        ConvertFormat::Webm => {
+            command.arg("-vf").arg(scale).args(["-c:v", "libvpx-vp9", "-b:v", "0", "-crf", "35", "-an"]);
+        },
+    }
+    let output = command.arg(&dst)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(output.stderr())
+    }
+    if !options.keep_original {
+        fs::remove_file(src).map_err(|e| e.to_string())?;
+    }
+    return Ok(dst)
+}
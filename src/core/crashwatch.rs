@@ -0,0 +1,293 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::apks::get_version;
+use crate::core::config::Config;
+use crate::core::ext::{OutputExt, PrintExt, ResultExt};
+use crate::core::hook::HookContext;
+use crate::core::logview::{is_process_of, LogLine, THREADTIME};
+use crate::core::r#const::{CRASHWATCH, SHELL};
+use crate::core::screencap::capture;
+use crate::core::selector::{adb_args_with, resolve_device, run_adb_with};
+use crate::core::strings::*;
+use crate::core::system::{catch_interrupts, interrupted, release_interrupts};
+use crate::core::template::Template;
+use itertools::Itertools;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const LOGCAT_ARGS: &[&str] = &["logcat", "-v", "threadtime", "-T", "1", "-b", "main,system,crash"];
+const HISTORY: usize = 2000;
+// the trace lines stop coming, the lines after the event are in the history by then
+const SETTLE: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const ANDROID_RUNTIME: &str = "AndroidRuntime";
+const FATAL_EXCEPTION: &str = "FATAL EXCEPTION";
+const DEBUG: &str = "DEBUG";
+const NATIVE_HEADER: &str = "*** *** ***";
+const ACTIVITY_MANAGER: &str = "ActivityManager";
+const JAVA_PROCESS: &str = r"^Process: ([^\s,]+), PID: \d+";
+const NATIVE_PROCESS: &str = r">>> ([^\s]+) <<<";
+const TOMBSTONE_WRITTEN: &str = r"Tombstone written to: (\S+)";
+const ANR_IN: &str = r"^ANR in ([^\s]+)";
+const ANR_DIR: &str = "/data/anr";
+const TRACE_FILE: &str = "trace.txt";
+const LOGCAT_FILE: &str = "logcat.txt";
+const SCREENSHOT_FILE: &str = "screenshot.png";
+const TOMBSTONE_FILE: &str = "tombstone.txt";
+const ANR_FILE: &str = "anr.txt";
+const DEVICE_FILE: &str = "device.txt";
+const DEVICE_PROPS: &str = "getprop ro.build.fingerprint; getprop ro.product.cpu.abi";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Kind {
+    Crash,
+    NativeCrash,
+    Anr,
+}
+
+impl Kind {
+    fn label(&self) -> &'static Label<'static> {
+        match self {
+            Kind::Crash => &KIND_CRASH,
+            Kind::NativeCrash => &KIND_NATIVE_CRASH,
+            Kind::Anr => &KIND_ANR,
+        }
+    }
+}
+
+/// A crash or an ANR with the lines of its trace, they come from the same pid and tag.
+struct Event {
+    kind: Kind,
+    time: String,
+    pid: u32,
+    tag: String,
+    package: Option<String>,
+    trace: Vec<String>,
+    tombstone: Option<String>,
+    last: Instant,
+}
+
+/// The patterns of the trace lines.
+struct Patterns {
+    line: Regex,
+    java_process: Regex,
+    native_process: Regex,
+    tombstone: Regex,
+    anr: Regex,
+}
+
+impl Event {
+    fn start(line: &LogLine, raw: &str, patterns: &Patterns) -> Option<Event> {
+        let (kind, package) = match line.tag.as_str() {
+            ANDROID_RUNTIME if line.message.starts_with(FATAL_EXCEPTION) => (Kind::Crash, None),
+            DEBUG if line.message.contains(NATIVE_HEADER) => (Kind::NativeCrash, None),
+            ACTIVITY_MANAGER => (Kind::Anr, Some(patterns.anr.captures(&line.message)?[1].to_string())),
+            _ => return None,
+        };
+        return Some(Event {
+            kind,
+            time: line.time.clone(),
+            pid: line.pid,
+            tag: line.tag.clone(),
+            package,
+            trace: vec![raw.to_string()],
+            tombstone: None,
+            last: Instant::now(),
+        })
+    }
+
+    /// Takes the line if it belongs to the trace, the ANR report is logged at once.
+    fn feed(&mut self, line: &LogLine, raw: &str, patterns: &Patterns) -> bool {
+        if line.pid != self.pid || line.tag != self.tag || (self.kind == Kind::Anr && line.time != self.time) {
+            return false
+        }
+        if self.package.is_none() {
+            self.package = patterns.java_process.captures(&line.message)
+                .or_else(|| patterns.native_process.captures(&line.message))
+                .map(|it| it[1].to_string());
+        }
+        if let Some(captures) = patterns.tombstone.captures(&line.message) {
+            self.tombstone = Some(captures[1].to_string());
+        }
+        self.trace.push(raw.to_string());
+        self.last = Instant::now();
+        return true
+    }
+
+    fn package(&self) -> String {
+        self.package.clone().unwrap_or(UNKNOWN.value().to_string())
+    }
+}
+
+/// The `crashwatch` command, writes a report folder for each crash or ANR and runs the hooks with it.
+pub fn crashwatch(args: &[String], config: &Config) -> ExitCode {
+    let package = args.first().cloned();
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    match &package {
+        Some(package) => CRASH_WATCHING.println_formatted(&[package]),
+        None => CRASH_WATCHING_ALL.println(),
+    }
+    return match watch(&device, package.as_deref(), config) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            e.eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn watch(device: &AdbDevice, package: Option<&str>, config: &Config) -> Result<(), String> {
+    let patterns = Patterns {
+        line: Regex::new(THREADTIME).unwrap(),
+        java_process: Regex::new(JAVA_PROCESS).unwrap(),
+        native_process: Regex::new(NATIVE_PROCESS).unwrap(),
+        tombstone: Regex::new(TOMBSTONE_WRITTEN).unwrap(),
+        anr: Regex::new(ANR_IN).unwrap(),
+    };
+    let mut child = adb_args_with(device, AdbArgs::spawn(LOGCAT_ARGS))
+        .command()?
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .string_err()?;
+    let stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break
+            }
+        }
+    });
+    // Ctrl-C stops adb as well, the pending event is reported anyway
+    catch_interrupts();
+    let mut history = VecDeque::with_capacity(HISTORY);
+    let mut event: Option<Event> = None;
+    loop {
+        let raw = match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(raw) => Some(raw),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if interrupted() {
+            break
+        }
+        if let Some(raw) = raw {
+            if history.len() == HISTORY {
+                history.pop_front();
+            }
+            history.push_back(raw.clone());
+            if let Some(line) = LogLine::parse(&raw, &patterns.line) {
+                let taken = event.as_mut().is_some_and(|it| it.feed(&line, &raw, &patterns));
+                let started = match taken {
+                    true => None,
+                    false => Event::start(&line, &raw, &patterns),
+                };
+                if let Some(started) = started {
+                    if let Some(done) = event.replace(started) {
+                        handle(device, config, done, &history, package);
+                    }
+                }
+            }
+        }
+        if event.as_ref().is_some_and(|it| it.last.elapsed() >= SETTLE) {
+            handle(device, config, event.take().unwrap(), &history, package);
+        }
+    }
+    if let Some(done) = event.take() {
+        handle(device, config, done, &history, package);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    release_interrupts();
+    return Ok(())
+}
+
+/// The events of other apps are skipped when a package is given.
+fn handle(device: &AdbDevice, config: &Config, event: Event, history: &VecDeque<String>, package: Option<&str>) {
+    let of_package = match (package, &event.package) {
+        (None, _) => true,
+        (Some(package), Some(name)) => is_process_of(name, package),
+        (Some(_), None) => false,
+    };
+    if !of_package {
+        return
+    }
+    CRASH_DETECTED.println_formatted(&[event.kind.label().value(), &event.package(), &event.pid.to_string()]);
+    let (dir, files) = match report(device, config, &event, history) {
+        Ok(report) => report,
+        Err(e) => {
+            e.eprintln();
+            return
+        }
+    };
+    SAVED.println_formatted(&[&dir.to_string_lossy()]);
+    let context = HookContext::new(CRASHWATCH, device, &dir).post(&files);
+    config.crash_hooks().run(&context);
+}
+
+fn report(device: &AdbDevice, config: &Config, event: &Event, history: &VecDeque<String>) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    let dir = Template::new(device, config).file(&config.crashes.destination, "", &config.crashes.name);
+    fs::create_dir_all(&dir).string_err()?;
+    let mut files = vec![];
+    let mut write = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        let path = dir.join(name);
+        fs::write(&path, bytes).string_err()?;
+        files.push(path);
+        return Ok(())
+    };
+    write(TRACE_FILE, event.trace.join("\n").as_bytes())?;
+    write(LOGCAT_FILE, history.iter().join("\n").as_bytes())?;
+    if let Ok(png) = capture(device) {
+        write(SCREENSHOT_FILE, &png)?;
+    }
+    // both are readable on userdebug builds and emulators only
+    let dump = match (event.kind, &event.tombstone) {
+        (Kind::NativeCrash, Some(tombstone)) => Some((TOMBSTONE_FILE, format!("cat {tombstone}"))),
+        (Kind::Anr, _) => Some((ANR_FILE, format!("cat {ANR_DIR}/$(ls -t {ANR_DIR} | head -n 1)"))),
+        _ => None,
+    };
+    if let Some((name, command)) = dump {
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+        if output.status.success() && !output.stdout.is_empty() {
+            write(name, &output.stdout)?;
+        }
+    }
+    write(DEVICE_FILE, device_info(device, event).as_bytes())?;
+    return Ok((dir, files))
+}
+
+fn device_info(device: &AdbDevice, event: &Event) -> String {
+    let props = run_adb_with(device, AdbArgs::run(&[SHELL, DEVICE_PROPS])).stdout();
+    let mut props = props.lines();
+    let mut lines = vec![
+        format!("event: {}", event.kind.label().value()),
+        format!("time: {}", event.time),
+        format!("package: {}", event.package()),
+        format!("pid: {}", event.pid),
+        format!("device: {}", device.description),
+        format!("serial: {}", device.serial),
+        format!("sdk: {}", device.sdk.map(|it| it.to_string()).unwrap_or(UNKNOWN.value().to_string())),
+        format!("fingerprint: {}", props.next().unwrap_or_default()),
+        format!("abi: {}", props.next().unwrap_or_default()),
+    ];
+    if let Some(package) = &event.package {
+        let version = get_version(device, package);
+        lines.push(format!("version: {} ({})",
+            version.name.unwrap_or(UNKNOWN.value().to_string()),
+            version.code.unwrap_or(UNKNOWN.value().to_string()),
+        ));
+    }
+    return lines.join("\n")
+}
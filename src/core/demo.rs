@@ -0,0 +1,126 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::{Config, Demo};
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::r#const::{DEMO, NULL, ON, SHELL};
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::INVALID_OPTION_VALUE;
+use std::process::ExitCode;
+use std::thread::sleep;
+use std::time::Duration;
+
+const GET_ALLOWED: &str = "settings get global sysui_demo_allowed";
+const PUT_ALLOWED: &str = "settings put global sysui_demo_allowed";
+const DELETE_ALLOWED: &str = "settings delete global sysui_demo_allowed";
+const GET_DEMO_ON: &str = "settings get global sysui_tuner_demo_on";
+const BROADCAST: &str = "am broadcast -a com.android.systemui.demo -e command";
+const ARG_ON: &str = "on";
+const ARG_OFF: &str = "off";
+// the status bar needs a moment to redraw
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+/// The demo mode turned on for a capture, `exit()` brings back what was before.
+pub struct DemoMode {
+    allowed: String,
+}
+
+pub fn demo(arg: Option<String>, config: &Config) -> ExitCode {
+    let on = match arg.as_deref() {
+        Some(ARG_ON) => Some(true),
+        Some(ARG_OFF) => Some(false),
+        None => None,
+        Some(arg) => {
+            INVALID_OPTION_VALUE.formatted(&[DEMO, arg]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let on = on.unwrap_or_else(|| !is_demo_on(&device));
+    let result = match on {
+        true => enter_demo(&device, &config.demo),
+        false => exit_demo(&device),
+    };
+    return match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            e.eprintln();
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl DemoMode {
+    /// None if the demo mode is already on, then it's left as is.
+    pub fn enter(device: &AdbDevice, demo: &Demo) -> Result<Option<DemoMode>, String> {
+        if is_demo_on(device) {
+            return Ok(None)
+        }
+        let allowed = shell(device, GET_ALLOWED)?;
+        enter_demo(device, demo)?;
+        sleep(SETTLE_DELAY);
+        return Ok(Some(DemoMode { allowed }))
+    }
+
+    pub fn exit(self, device: &AdbDevice) {
+        let restore = match self.allowed.as_str() {
+            ON => None,
+            NULL | "" => Some(DELETE_ALLOWED.to_string()),
+            value => Some(format!("{PUT_ALLOWED} {value}")),
+        };
+        let result = exit_demo(device)
+            .and_then(|_| restore.map(|it| shell(device, &it)).transpose());
+        if let Err(e) = result {
+            e.eprintln();
+        }
+    }
+}
+
+fn is_demo_on(device: &AdbDevice) -> bool {
+    run_adb_with(device, AdbArgs::run(&[SHELL, GET_DEMO_ON])).stdout() == ON
+}
+
+fn enter_demo(device: &AdbDevice, demo: &Demo) -> Result<(), String> {
+    let commands = demo_commands(demo)?;
+    return shell(device, &commands.join(" && ")).map(|_| ())
+}
+
+fn exit_demo(device: &AdbDevice) -> Result<(), String> {
+    return shell(device, &format!("{BROADCAST} exit")).map(|_| ())
+}
+
+fn demo_commands(demo: &Demo) -> Result<Vec<String>, String> {
+    if demo.clock.len() != 4 || !demo.clock.chars().all(|it| it.is_ascii_digit()) {
+        return Err(INVALID_OPTION_VALUE.formatted(&["demo.clock", &demo.clock]))
+    }
+    if let Some(data_type) = demo.mobile_type.as_ref().filter(|it| !it.chars().all(|c| c.is_ascii_alphanumeric() || c == '+')) {
+        return Err(INVALID_OPTION_VALUE.formatted(&["demo.mobile_type", data_type]))
+    }
+    let level = |level: Option<u8>| match level {
+        Some(level) => format!("show -e level {}", level.min(4)),
+        None => "hide".to_string(),
+    };
+    let mut mobile = format!("{BROADCAST} network -e mobile {}", level(demo.mobile));
+    if let Some(data_type) = &demo.mobile_type {
+        mobile = format!("{mobile} -e datatype {data_type}");
+    }
+    return Ok(vec![
+        format!("{PUT_ALLOWED} {ON}"),
+        format!("{BROADCAST} enter"),
+        format!("{BROADCAST} clock -e hhmm {}", demo.clock),
+        format!("{BROADCAST} battery -e level {} -e plugged {}", demo.battery.min(100), demo.plugged),
+        format!("{BROADCAST} network -e wifi {}", level(demo.wifi)),
+        mobile,
+        format!("{BROADCAST} notifications -e visible {}", demo.notifications),
+    ])
+}
+
+fn shell(device: &AdbDevice, command: &str) -> Result<String, String> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command]));
+    return match output.status.success() {
+        true => Ok(output.stdout()),
+        false => Err(output.stderr()),
+    }
+}
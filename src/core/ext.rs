@@ -221,7 +221,6 @@ impl<T> VecExt<T> for Vec<T> {
 
 pub trait StrExt {
     fn last_index(&self) -> usize;
-    fn index_of(&self, c: char) -> Option<usize>;
     fn last_index_of(&self, c: char) -> Option<usize>;
     fn file_name(&self) -> String;
     fn path(&self) -> PathBuf;
@@ -248,10 +247,6 @@ impl StrExt for str {
         self.len() - 1
     }
 
-    fn index_of(&self, c: char) -> Option<usize> {
-        inner_index_of(self, c, false)
-    }
-
     fn last_index_of(&self, c: char) -> Option<usize> {
         inner_index_of(self, c, true)
     }
@@ -0,0 +1,63 @@
+use crate::core::strings::{NO_OPTION_VALUE, UNKNOWN_OPTION};
+
+const PREFIX: &str = "--";
+
+/// Command arguments split into positional ones and `--options`.
+pub struct Flags {
+    pub positional: Vec<String>,
+    named: Vec<(String, Option<String>)>,
+}
+
+impl Flags {
+    /// `switches` go without a value, `options` take the next argument or the one after `=`.
+    pub fn parse(args: &[String], switches: &[&str], options: &[&str]) -> Result<Flags, String> {
+        let mut positional = vec![];
+        let mut named = vec![];
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = match arg.strip_prefix(PREFIX) {
+                Some(name) if !name.is_empty() => name,
+                _ => {
+                    positional.push(arg.clone());
+                    continue
+                }
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (name, None),
+            };
+            match () {
+                _ if switches.contains(&name) && value.is_none() => named.push((name.to_string(), None)),
+                _ if options.contains(&name) => {
+                    let value = value.or_else(|| iter.next().cloned())
+                        .ok_or_else(|| NO_OPTION_VALUE.formatted(&[arg]))?;
+                    named.push((name.to_string(), Some(value)));
+                },
+                _ => return Err(UNKNOWN_OPTION.formatted(&[arg])),
+            }
+        }
+        return Ok(Flags { positional, named })
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.named.iter().any(|(it, _)| it == name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.named.iter()
+            .rev()
+            .find(|(it, _)| it == name)
+            .and_then(|(_, value)| value.clone())
+    }
+
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.named.iter()
+            .filter(|(it, _)| it == name)
+            .filter_map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    pub fn arg(&self, index: usize) -> Option<String> {
+        self.positional.get(index).cloned()
+    }
+}
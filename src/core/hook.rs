@@ -0,0 +1,158 @@
+use crate::core::adb_device::AdbDevice;
+use crate::core::ext::{OutputExt, PathBufExt, PrintExt};
+use crate::core::strings::{ADD_INTERPRETER, HOOK_TIMED_OUT};
+use serde_derive::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitCode, Stdio};
+use std::thread;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const ENV_EVENT: &str = "ADB_EXT_EVENT";
+const ENV_COMMAND: &str = "ADB_EXT_COMMAND";
+const ENV_SERIAL: &str = "ADB_EXT_SERIAL";
+const ENV_MODEL: &str = "ADB_EXT_MODEL";
+const ENV_SDK: &str = "ADB_EXT_SDK";
+const ENV_FILES: &str = "ADB_EXT_FILES";
+const ENV_DESTINATION: &str = "ADB_EXT_DESTINATION";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+#[cfg(unix)]
+const SHEBANG: &[u8] = b"#!";
+#[cfg(unix)]
+const BINARY_MAGICS: &[&[u8]] = &[b"\x7FELF", &[0xCF, 0xFA, 0xED, 0xFE], &[0xCE, 0xFA, 0xED, 0xFE], &[0xCA, 0xFE, 0xBA, 0xBE]];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookEvent {
+    Pre,
+    Post,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &str {
+        match self {
+            HookEvent::Pre => "pre",
+            HookEvent::Post => "post",
+        }
+    }
+}
+
+/// The existing hook files of a command.
+pub struct HookSet {
+    pub pre: Vec<PathBuf>,
+    pub post: Vec<PathBuf>,
+    pub timeout: Duration,
+}
+
+/// The document a hook receives on stdin, the same values are passed as ADB_EXT_* variables.
+#[derive(Serialize)]
+pub struct HookContext {
+    pub event: HookEvent,
+    pub command: String,
+    pub serial: Option<String>,
+    pub model: Option<String>,
+    pub sdk: Option<usize>,
+    pub files: Vec<String>,
+    pub destination: Option<String>,
+}
+
+impl HookContext {
+    pub fn new(command: &str, device: &AdbDevice, destination: &PathBuf) -> HookContext {
+        HookContext {
+            event: HookEvent::Pre,
+            command: command.to_string(),
+            serial: Some(device.serial.clone()),
+            model: Some(device.model.clone()),
+            sdk: device.sdk,
+            files: vec![],
+            destination: Some(destination.to_string()),
+        }
+    }
+
+    pub fn post(mut self, files: &[PathBuf]) -> HookContext {
+        self.event = HookEvent::Post;
+        self.files = files.iter().map(|it| it.to_string()).collect();
+        return self
+    }
+}
+
+impl HookSet {
+    /// Runs the hooks of the context event one by one, stops at the first failed one.
+    pub fn run(&self, context: &HookContext) -> ExitCode {
+        let hooks = match context.event {
+            HookEvent::Pre => &self.pre,
+            HookEvent::Post => &self.post,
+        };
+        for hook in hooks {
+            let code = run_hook(hook, context, self.timeout).unwrap_or_else(|e| {
+                e.eprintln();
+                ExitCode::FAILURE
+            });
+            if code != ExitCode::SUCCESS {
+                return code
+            }
+        }
+        return ExitCode::SUCCESS
+    }
+}
+
+fn run_hook(hook: &PathBuf, context: &HookContext, timeout: Duration) -> Result<ExitCode, String> {
+    #[cfg(unix)]
+    check_interpreter(hook)?;
+    let json = serde_json::to_string(context).map_err(|e| e.to_string())?;
+    let mut command = Command::new(hook);
+    command.arg(&context.command)
+        .args(&context.files)
+        .env(ENV_EVENT, context.event.name())
+        .env(ENV_COMMAND, &context.command)
+        .env(ENV_FILES, context.files.join("\n"))
+        .stdin(Stdio::piped());
+    let optional = [
+        (ENV_SERIAL, context.serial.clone()),
+        (ENV_MODEL, context.model.clone()),
+        (ENV_SDK, context.sdk.map(|it| it.to_string())),
+        (ENV_DESTINATION, context.destination.clone()),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value {
+            command.env(name, value);
+        }
+    }
+    let mut child = command.spawn().map_err(|e| format!("{}: {e}", hook.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the hook isn't obliged to read it, so don't block on a full pipe
+        thread::spawn(move || stdin.write_all(json.as_bytes()));
+    }
+    return wait_with_timeout(child, timeout)
+        .ok_or_else(|| HOOK_TIMED_OUT.formatted(&[&hook.to_string()]))
+}
+
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Option<ExitCode> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return child.wait_with_output().ok().map(|it| it.exit_code()),
+            Ok(None) if start.elapsed() < timeout => sleep(POLL_INTERVAL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None
+            },
+        }
+    }
+}
+
+#[cfg(unix)]
+fn check_interpreter(hook: &PathBuf) -> Result<(), String> {
+    use std::io::Read;
+    let mut bytes = Vec::with_capacity(4);
+    std::fs::File::open(hook)
+        .and_then(|file| file.take(4).read_to_end(&mut bytes))
+        .map_err(|e| e.to_string())?;
+    let known = bytes.starts_with(SHEBANG) || BINARY_MAGICS.iter().any(|it| bytes.starts_with(it));
+    return match known {
+        true => Ok(()),
+        false => Err(format!("{}: {ADD_INTERPRETER}", hook.to_string())),
+    }
+}
@@ -0,0 +1,106 @@
+use crate::core::strings::*;
+use regex::Regex;
+
+// Failure [INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected: …]
+const FAILURE_CODE: &str = r"INSTALL_(?:PARSE_)?FAILED_[A-Z0-9_]+";
+const PARSE_FAILED: &str = "INSTALL_PARSE_FAILED_";
+
+/// The failure codes of `adb install` with an explanation and maybe a fix.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstallError {
+    UpdateIncompatible,
+    VersionDowngrade,
+    TestOnly,
+    InsufficientStorage,
+    OlderSdk,
+    DeprecatedSdk,
+    NoMatchingAbis,
+    MissingSharedLibrary,
+    DuplicatePermission,
+    UserRestricted,
+    Aborted,
+    NoCertificates,
+    ParseFailed(String),
+    Other(String),
+}
+
+/// What `run` can retry the installation with.
+pub enum InstallFix {
+    Uninstall,
+    Flag(&'static str),
+}
+
+impl InstallError {
+    /// Finds the failure code in the output of `adb install`.
+    pub fn parse(output: &str) -> Option<InstallError> {
+        let code = Regex::new(FAILURE_CODE).unwrap()
+            .find(output)?
+            .as_str()
+            .to_string();
+        let error = match code.as_str() {
+            "INSTALL_FAILED_UPDATE_INCOMPATIBLE" => InstallError::UpdateIncompatible,
+            "INSTALL_FAILED_VERSION_DOWNGRADE" => InstallError::VersionDowngrade,
+            "INSTALL_FAILED_TEST_ONLY" => InstallError::TestOnly,
+            "INSTALL_FAILED_INSUFFICIENT_STORAGE" => InstallError::InsufficientStorage,
+            "INSTALL_FAILED_OLDER_SDK" => InstallError::OlderSdk,
+            "INSTALL_FAILED_DEPRECATED_SDK_VERSION" => InstallError::DeprecatedSdk,
+            "INSTALL_FAILED_NO_MATCHING_ABIS" => InstallError::NoMatchingAbis,
+            "INSTALL_FAILED_MISSING_SHARED_LIBRARY" => InstallError::MissingSharedLibrary,
+            "INSTALL_FAILED_DUPLICATE_PERMISSION" => InstallError::DuplicatePermission,
+            "INSTALL_FAILED_USER_RESTRICTED" => InstallError::UserRestricted,
+            "INSTALL_FAILED_ABORTED" => InstallError::Aborted,
+            "INSTALL_PARSE_FAILED_NO_CERTIFICATES" => InstallError::NoCertificates,
+            _ if code.starts_with(PARSE_FAILED) => InstallError::ParseFailed(code),
+            _ => InstallError::Other(code),
+        };
+        return Some(error)
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            InstallError::UpdateIncompatible => "INSTALL_FAILED_UPDATE_INCOMPATIBLE",
+            InstallError::VersionDowngrade => "INSTALL_FAILED_VERSION_DOWNGRADE",
+            InstallError::TestOnly => "INSTALL_FAILED_TEST_ONLY",
+            InstallError::InsufficientStorage => "INSTALL_FAILED_INSUFFICIENT_STORAGE",
+            InstallError::OlderSdk => "INSTALL_FAILED_OLDER_SDK",
+            InstallError::DeprecatedSdk => "INSTALL_FAILED_DEPRECATED_SDK_VERSION",
+            InstallError::NoMatchingAbis => "INSTALL_FAILED_NO_MATCHING_ABIS",
+            InstallError::MissingSharedLibrary => "INSTALL_FAILED_MISSING_SHARED_LIBRARY",
+            InstallError::DuplicatePermission => "INSTALL_FAILED_DUPLICATE_PERMISSION",
+            InstallError::UserRestricted => "INSTALL_FAILED_USER_RESTRICTED",
+            InstallError::Aborted => "INSTALL_FAILED_ABORTED",
+            InstallError::NoCertificates => "INSTALL_PARSE_FAILED_NO_CERTIFICATES",
+            InstallError::ParseFailed(code) | InstallError::Other(code) => code,
+        }
+    }
+
+    pub fn explanation(&self) -> Option<&'static Label<'static>> {
+        let label = match self {
+            InstallError::UpdateIncompatible => &INSTALL_UPDATE_INCOMPATIBLE,
+            InstallError::VersionDowngrade => &INSTALL_VERSION_DOWNGRADE,
+            InstallError::TestOnly => &INSTALL_TEST_ONLY,
+            InstallError::InsufficientStorage => &INSTALL_INSUFFICIENT_STORAGE,
+            InstallError::OlderSdk => &INSTALL_OLDER_SDK,
+            InstallError::DeprecatedSdk => &INSTALL_DEPRECATED_SDK,
+            InstallError::NoMatchingAbis => &INSTALL_NO_MATCHING_ABIS,
+            InstallError::MissingSharedLibrary => &INSTALL_MISSING_SHARED_LIBRARY,
+            InstallError::DuplicatePermission => &INSTALL_DUPLICATE_PERMISSION,
+            InstallError::UserRestricted => &INSTALL_USER_RESTRICTED,
+            InstallError::Aborted => &INSTALL_ABORTED,
+            InstallError::NoCertificates => &INSTALL_NO_CERTIFICATES,
+            InstallError::ParseFailed(_) => &INSTALL_PARSE_FAILED,
+            InstallError::Other(_) => return None,
+        };
+        return Some(label)
+    }
+
+    /// The fix and the question to ask before applying it.
+    pub fn fix(&self) -> Option<(InstallFix, &'static Label<'static>)> {
+        match self {
+            InstallError::UpdateIncompatible => Some((InstallFix::Uninstall, &UNINSTALL_AND_RETRY)),
+            InstallError::VersionDowngrade => Some((InstallFix::Flag("-d"), &RETRY_DOWNGRADE)),
+            InstallError::TestOnly => Some((InstallFix::Flag("-t"), &RETRY_TEST_ONLY)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::axml::class_name;
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::flags::Flags;
+use crate::core::packages::{package_or_pick, PackageFilter};
+use crate::core::r#const::SHELL;
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::{INVALID_OPTION_VALUE, LAUNCH_TIMES, NO_LAUNCHER_ACTIVITY};
+use std::process::ExitCode;
+
+const DEEPLINK: &str = "deeplink";
+const ACTIVITY: &str = "activity";
+const EXTRA: &str = "extra";
+const DEBUG: &str = "debug";
+const WAIT: &str = "wait";
+pub const SWITCHES: [&str; 2] = [DEBUG, WAIT];
+pub const OPTIONS: [&str; 3] = [DEEPLINK, ACTIVITY, EXTRA];
+const ACTION_MAIN: &str = "android.intent.action.MAIN";
+const ACTION_VIEW: &str = "android.intent.action.VIEW";
+const RESOLVE_LAUNCHER: &str = "cmd package resolve-activity --brief -c android.intent.category.LAUNCHER";
+const TOTAL_TIME: &str = "TotalTime:";
+const WAIT_TIME: &str = "WaitTime:";
+// type names of --extra and the matching am flags
+const EXTRA_TYPES: &[(&[&str], &str)] = &[
+    (&["s", "string"], "--es"),
+    (&["i", "int"], "--ei"),
+    (&["l", "long"], "--el"),
+    (&["f", "float"], "--ef"),
+    (&["d", "double"], "--ed"),
+    (&["b", "bool", "boolean"], "--ez"),
+    (&["u", "uri"], "--eu"),
+];
+
+/// How `am start` is called by `run` and `launch`.
+#[derive(Default)]
+pub struct LaunchOptions {
+    pub deeplink: Option<String>,
+    pub activity: Option<String>,
+    /// am flag, key, value
+    pub extras: Vec<(&'static str, String, String)>,
+    pub debug: bool,
+    pub wait: bool,
+}
+
+impl LaunchOptions {
+    pub fn from_flags(flags: &Flags) -> Result<LaunchOptions, String> {
+        let extras = flags.values(EXTRA)
+            .iter()
+            .map(|it| parse_extra(it).ok_or_else(|| INVALID_OPTION_VALUE.formatted(&[&format!("--{EXTRA}"), it])))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(LaunchOptions {
+            deeplink: flags.value(DEEPLINK),
+            activity: flags.value(ACTIVITY),
+            extras,
+            debug: flags.has(DEBUG),
+            wait: flags.has(WAIT),
+        })
+    }
+
+    /// The `am start` command, `default_activity` is left out for a deep link.
+    pub fn start_command(&self, package: &str, default_activity: Option<String>) -> Option<String> {
+        let activity = match (&self.activity, &self.deeplink) {
+            (Some(activity), _) => Some(class_name(package, activity)),
+            (None, Some(_)) => None,
+            (None, None) => Some(default_activity?),
+        };
+        return Some(self.am_start(package, activity))
+    }
+
+    fn am_start(&self, package: &str, activity: Option<String>) -> String {
+        let mut args = vec!["am start".to_string()];
+        if self.debug {
+            args.push("-D".to_string());
+        }
+        if self.wait {
+            args.push("-W".to_string());
+        }
+        let component = activity.map(|it| format!("-n {}", quote(&format!("{package}/{it}"))));
+        match (&self.deeplink, component) {
+            (Some(uri), component) => {
+                args.push(format!("-a {ACTION_VIEW} -d {}", quote(uri)));
+                args.push(component.unwrap_or_else(|| format!("-p {}", quote(package))));
+            },
+            (None, component) => {
+                args.push(format!("-a {ACTION_MAIN}"));
+                args.extend(component);
+            },
+        }
+        for (flag, key, value) in &self.extras {
+            args.push(format!("{flag} {} {}", quote(key), quote(value)));
+        }
+        return args.join(" ")
+    }
+}
+
+/// `key:type=value`, or `key=value` for a string.
+fn parse_extra(extra: &str) -> Option<(&'static str, String, String)> {
+    let (key, value) = extra.split_once('=')?;
+    let (key, type_name) = key.split_once(':').unwrap_or((key, "s"));
+    let flag = EXTRA_TYPES.iter()
+        .find(|(names, _)| names.contains(&type_name.to_lowercase().as_str()))
+        .map(|(_, flag)| *flag)?;
+    return match key.is_empty() {
+        true => None,
+        false => Some((flag, key.to_string(), value.to_string())),
+    }
+}
+
+fn quote(value: &str) -> String {
+    shell_words::quote(value).into_owned()
+}
+
+/// The `launch` command for the installed apps.
+pub fn launch(args: &[String]) -> ExitCode {
+    let flags = match Flags::parse(args, &SWITCHES, &OPTIONS) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let options = match LaunchOptions::from_flags(&flags) {
+        Ok(options) => options,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let package = match package_or_pick(&device, flags.arg(0), &PackageFilter::default()) {
+        Ok(package) => package,
+        Err(code) => return code,
+    };
+    let activity = match options.activity.is_some() || options.deeplink.is_some() {
+        true => None,
+        false => launcher_activity(&device, &package),
+    };
+    return launch_app(&device, &package, activity, &options)
+}
+
+/// `default_activity` is used if neither an activity nor a deep link is passed.
+pub fn launch_app(device: &AdbDevice, package: &str, default_activity: Option<String>, options: &LaunchOptions) -> ExitCode {
+    let command = match options.start_command(package, default_activity) {
+        Some(command) => command,
+        None => {
+            NO_LAUNCHER_ACTIVITY.formatted(&[package]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    if !options.wait {
+        return run_adb_with(device, AdbArgs::spawn(&[SHELL, command.as_str()])).exit_code()
+    }
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+    let stdout = output.stdout();
+    let time = |prefix: &str| stdout.lines()
+        .find_map(|it| it.trim().strip_prefix(prefix))
+        .map(|it| it.trim().to_string());
+    match (time(TOTAL_TIME), time(WAIT_TIME)) {
+        (None, None) => output.print_out_and_err(),
+        (total, wait) => LAUNCH_TIMES.println_formatted(&[
+            &total.unwrap_or_default(),
+            &wait.unwrap_or_default(),
+        ]),
+    }
+    return output.exit_code()
+}
+
+/// Resolves the launcher activity of an installed package.
+pub fn launcher_activity(device: &AdbDevice, package: &str) -> Option<String> {
+    let command = format!("{RESOLVE_LAUNCHER} {}", quote(package));
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+    // the last line is "app.package.name/.MainActivity"
+    return output.stdout()
+        .lines()
+        .last()
+        .and_then(|it| it.trim().split_once('/'))
+        .filter(|(it, _)| *it == package)
+        .map(|(_, activity)| activity.to_string())
+}
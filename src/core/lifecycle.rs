@@ -0,0 +1,127 @@
+use crate::core::activity::focused_activity;
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::axml::class_name;
+use crate::core::ext::OutputExt;
+use crate::core::launch::{launch_app, launcher_activity, LaunchOptions};
+use crate::core::packages::{pick_package, PackageFilter};
+use crate::core::r#const::SHELL;
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::{Label, CLEAR_DATA_OF, DATA_CLEARED, KILLED, RESTARTED, STOPPED, UNINSTALL_PACKAGE, UNINSTALLED};
+use crate::core::util::interactive_confirm;
+use std::process::ExitCode;
+
+const PM_UNINSTALL: &str = "pm uninstall";
+const PM_CLEAR: &str = "pm clear";
+const AM_FORCE_STOP: &str = "am force-stop";
+const AM_KILL: &str = "am kill";
+const GO_HOME: &str = "input keyevent KEYCODE_HOME";
+const FAILURE: &str = "Failure";
+const RESOLVE_HOME: &str = "cmd package resolve-activity --brief -a android.intent.action.MAIN -c android.intent.category.HOME";
+
+pub fn kill(args: &[String]) -> ExitCode {
+    // am kill only kills the processes in the background, the status is the one of am kill
+    act(args, &PackageFilter::default(), None, &KILLED, |device, package, focused| {
+        let command = match focused {
+            Some(_) => format!("{GO_HOME}; {AM_KILL} {package}"),
+            None => format!("{AM_KILL} {package}"),
+        };
+        shell(device, &command)
+    })
+}
+
+pub fn clear_data(args: &[String]) -> ExitCode {
+    act(args, &PackageFilter::default(), Some(&CLEAR_DATA_OF), &DATA_CLEARED, |device, package, _| {
+        shell(device, &format!("{PM_CLEAR} {package}"))
+    })
+}
+
+pub fn stop(args: &[String]) -> ExitCode {
+    act(args, &PackageFilter::default(), None, &STOPPED, |device, package, _| {
+        shell(device, &format!("{AM_FORCE_STOP} {package}"))
+    })
+}
+
+pub fn uninstall(args: &[String]) -> ExitCode {
+    act(args, &PackageFilter::third_party(), Some(&UNINSTALL_PACKAGE), &UNINSTALLED, |device, package, _| {
+        shell(device, &format!("{PM_UNINSTALL} {package}"))
+    })
+}
+
+/// Relaunches the focused activity of the app or the launcher one.
+pub fn restart(args: &[String]) -> ExitCode {
+    act(args, &PackageFilter::default(), None, &RESTARTED, |device, package, focused| {
+        let activity = match focused {
+            Some(class) => Some(class_name(package, &class)),
+            None => launcher_activity(device, package),
+        };
+        let code = shell(device, &format!("{AM_FORCE_STOP} {package}"));
+        if code != ExitCode::SUCCESS {
+            return code
+        }
+        return launch_app(device, package, activity, &LaunchOptions::default())
+    })
+}
+
+/// Runs the action on the passed package, the focused one or the picked one, and prints the package.
+/// The launcher is never the focused one, `confirm` is asked before acting on the focused package.
+/// The action gets the focused activity if it belongs to the package.
+fn act<F>(args: &[String], filter: &PackageFilter, confirm: Option<&Label<'_>>, done: &Label<'_>, action: F) -> ExitCode
+where F: FnOnce(&AdbDevice, &str, Option<String>) -> ExitCode {
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let focused = focused_activity(&device);
+    let home = match (args.first(), &focused) {
+        (None, Some(_)) => home_package(&device),
+        _ => None,
+    };
+    let package = match (args.first(), &focused) {
+        (Some(package), _) => package.clone(),
+        (None, Some(focused)) if home.as_ref() != Some(&focused.package) => {
+            if let Some(question) = confirm {
+                if !interactive_confirm(&question.formatted(&[&focused.package])) {
+                    return ExitCode::FAILURE
+                }
+            }
+            focused.package.clone()
+        },
+        (None, _) => match pick_package(&device, filter) {
+            Ok(package) => package,
+            Err(code) => return code,
+        },
+    };
+    let focused = focused
+        .filter(|it| it.package == package)
+        .map(|it| it.class);
+    let code = action(&device, &package, focused);
+    if code == ExitCode::SUCCESS {
+        done.println_formatted(&[&package]);
+    }
+    return code
+}
+
+fn shell(device: &AdbDevice, command: &str) -> ExitCode {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command]));
+    // old versions of pm exit with 0 on failures
+    if !output.status.success() || output.stdout().starts_with(FAILURE) {
+        output.print_out_and_err();
+        return match output.status.success() {
+            true => ExitCode::FAILURE,
+            false => output.exit_code(),
+        }
+    }
+    return ExitCode::SUCCESS
+}
+
+/// The package of the launcher app.
+fn home_package(device: &AdbDevice) -> Option<String> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, RESOLVE_HOME]));
+    // the last line is "app.package.name/.LauncherActivity"
+    return output.stdout()
+        .lines()
+        .last()
+        .and_then(|it| it.trim().split_once('/'))
+        .map(|(package, _)| package.to_string())
+}
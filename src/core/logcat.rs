@@ -0,0 +1,103 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::ext::OutputExt;
+use crate::core::logview::THREADTIME;
+use crate::core::r#const::SHELL;
+use crate::core::selector::{adb_args_with, run_adb_with};
+use crate::core::strings::NOT_RUNNING;
+use chrono::{Datelike, NaiveDateTime};
+use regex::Regex;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+const LOGCAT: &str = "logcat";
+const THREADTIME_ARGS: &[&str] = &["-v", "threadtime"];
+const ONLY_NEW: &[&str] = &["-T", "1"];
+const SINCE: &str = "-T";
+const DEVICE_DATE: &str = "date '+%Y-%m-%d %H:%M:%S.%N'";
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+// the format of -T, the same as the one of the threadtime lines
+const SINCE_FORMAT: &str = "%m-%d %H:%M:%S%.3f";
+
+/// Saves the logcat lines prefixed with the offset from the start of a screencast.
+/// The offsets come from the device timestamps of the lines, the host clock is used for the lines without one.
+pub struct LogcatRecorder {
+    child: Child,
+    writer: JoinHandle<io::Result<()>>,
+}
+
+impl LogcatRecorder {
+    pub fn start(device: &AdbDevice, package: Option<&String>, dst: &PathBuf, start: Instant) -> Result<LogcatRecorder, String> {
+        let elapsed = start.elapsed();
+        let device_start = device_time(device);
+        let mut args = [&[LOGCAT], THREADTIME_ARGS].concat()
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<String>>();
+        match device_start {
+            Some(time) => {
+                // adb passes the args to the shell as one line
+                let since = time.format(SINCE_FORMAT).to_string();
+                args.extend([SINCE.to_string(), shell_words::quote(&since).into_owned()]);
+            },
+            None => args.extend(ONLY_NEW.iter().map(|it| it.to_string())),
+        }
+        if let Some(package) = package {
+            let pid = pid_of(device, package).ok_or_else(|| NOT_RUNNING.formatted(&[package]))?;
+            args.push(format!("--pid={pid}"));
+        }
+        let mut file = BufWriter::new(File::create(dst).map_err(|e| e.to_string())?);
+        let mut child = adb_args_with(device, AdbArgs::spawn(&args))
+            .command()?
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let stdout = child.stdout.take().unwrap();
+        let regex = Regex::new(THREADTIME).unwrap();
+        let writer = thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = line?;
+                let on_device = device_start.and_then(|device_start| {
+                    let time = regex.captures(&line)?;
+                    // the lines have no year, the recordings don't last over the new year
+                    let time = NaiveDateTime::parse_from_str(&format!("{}-{}", device_start.year(), &time[1]), DATE_FORMAT).ok()?;
+                    return Some((time - device_start).to_std().unwrap_or_default() + elapsed)
+                });
+                let offset = on_device.unwrap_or_else(|| Instant::now().saturating_duration_since(start)).as_millis();
+                let (minutes, millis) = (offset / 60_000, offset % 60_000);
+                writeln!(file, "+{minutes:02}:{:02}.{:03} {line}", millis / 1000, millis % 1000)?;
+            }
+            return file.flush()
+        });
+        return Ok(LogcatRecorder { child, writer })
+    }
+
+    pub fn stop(mut self) -> io::Result<()> {
+        self.child.kill()?;
+        self.child.wait()?;
+        return self.writer.join().unwrap()
+    }
+}
+
+/// None if the device `date` doesn't print the nanoseconds.
+fn device_time(device: &AdbDevice) -> Option<NaiveDateTime> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, DEVICE_DATE]));
+    return NaiveDateTime::parse_from_str(&output.stdout(), DATE_FORMAT).ok()
+}
+
+pub fn pid_of(device: &AdbDevice, package: &str) -> Option<String> {
+    let command = format!("pidof -s {package}");
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+    let pid = output.stdout();
+    return match output.status.success() && !pid.is_empty() {
+        true => Some(pid),
+        false => None,
+    }
+}
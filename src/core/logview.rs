@@ -0,0 +1,301 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::Config;
+use crate::core::ext::{OutputExt, PrintExt, ResultExt};
+use crate::core::flags::Flags;
+use crate::core::r#const::SHELL;
+use crate::core::selector::{adb_args_with, resolve_device, run_adb_with};
+use crate::core::strings::{LOG_PROCESS_DIED, LOG_PROCESS_STARTED, LOG_WAITING, SAVED, UNKNOWN_LEVEL};
+use crate::core::system::{catch_interrupts, interrupted, release_interrupts};
+use crate::core::template::Template;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
+use std::time::{Duration, Instant};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+const SAVE: &str = "save";
+const TAG: &str = "tag";
+const LEVEL: &str = "level";
+const GREP: &str = "grep";
+const LOGCAT_ARGS: &[&str] = &["logcat", "-v", "threadtime", "-T", "1"];
+const PS: &str = "ps -A -o PID,NAME 2>/dev/null || ps";
+// the lowest first, A is the assert of Log.wtf
+const LEVELS: &str = "VDIWEFA";
+pub const THREADTIME: &str = r"^(\d\d-\d\d \d\d:\d\d:\d\d\.\d+)\s+(\d+)\s+(\d+)\s+([VDIWEFA])\s+(.*?)\s*: ?(.*)$";
+const ACTIVITY_MANAGER: &str = "ActivityManager";
+const PROC_START: &str = r"Start proc (\d+):([^/\s]+)";
+const PROC_DIED: &str = r"Process ([^\s]+) \(pid (\d+)\) has died";
+const PS_REFRESH: Duration = Duration::from_secs(2);
+const NAME_WIDTH: usize = 24;
+const TAG_WIDTH: usize = 20;
+
+/// A line of `logcat -v threadtime`.
+#[derive(Debug, PartialEq)]
+pub struct LogLine {
+    pub time: String,
+    pub pid: u32,
+    pub tid: u32,
+    pub level: char,
+    pub tag: String,
+    pub message: String,
+}
+
+impl LogLine {
+    pub fn parse(line: &str, regex: &Regex) -> Option<LogLine> {
+        let captures = regex.captures(line)?;
+        return Some(LogLine {
+            time: captures[1].to_string(),
+            pid: captures[2].parse().ok()?,
+            tid: captures[3].parse().ok()?,
+            level: captures[4].chars().next()?,
+            tag: captures[5].to_string(),
+            message: captures[6].to_string(),
+        })
+    }
+}
+
+/// PID to process name, read from `ps` and kept up to date by the ActivityManager lines.
+struct Processes {
+    names: HashMap<u32, String>,
+    refreshed: Option<Instant>,
+}
+
+impl Processes {
+    fn name(&mut self, device: &AdbDevice, pid: u32) -> Option<&String> {
+        let stale = self.refreshed.is_none_or(|it| it.elapsed() >= PS_REFRESH);
+        if !self.names.contains_key(&pid) && stale {
+            self.refresh(device);
+        }
+        return self.names.get(&pid)
+    }
+
+    fn refresh(&mut self, device: &AdbDevice) {
+        self.refreshed = Some(Instant::now());
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, PS]));
+        for line in output.stdout().lines() {
+            let pid = line.split_whitespace().find_map(|it| it.parse::<u32>().ok());
+            if let (Some(pid), Some(name)) = (pid, line.split_whitespace().last()) {
+                self.names.insert(pid, name.to_string());
+            }
+        }
+    }
+}
+
+/// The filters of the `log` command, the package is matched by the process name, its services included.
+struct Filter {
+    package: Option<String>,
+    tags: Vec<String>,
+    level: usize,
+    grep: Option<Regex>,
+}
+
+impl Filter {
+    fn of_package(&self, name: &str) -> bool {
+        match &self.package {
+            None => true,
+            Some(package) => is_process_of(name, package),
+        }
+    }
+
+    fn accepts(&self, line: &LogLine) -> bool {
+        return level_index(line.level).unwrap_or(0) >= self.level
+            && (self.tags.is_empty() || self.tags.iter().any(|it| it.eq_ignore_ascii_case(&line.tag)))
+            && self.grep.as_ref().is_none_or(|it| it.is_match(&line.message) || it.is_match(&line.tag))
+    }
+}
+
+/// The `log` command, a colored logcat which follows the package across its restarts.
+pub fn log(args: &[String], config: &Config) -> ExitCode {
+    let flags = match Flags::parse(args, &[SAVE], &[TAG, LEVEL, GREP]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let level = match flags.value(LEVEL) {
+        None => 0,
+        Some(value) => match value.chars().next().and_then(|it| level_index(it.to_ascii_uppercase())) {
+            Some(level) => level,
+            None => {
+                UNKNOWN_LEVEL.formatted(&[&value]).eprintln();
+                return ExitCode::FAILURE
+            }
+        },
+    };
+    let grep = match flags.value(GREP).map(|it| Regex::new(&it)) {
+        None => None,
+        Some(Ok(regex)) => Some(regex),
+        Some(Err(e)) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let filter = Filter { package: flags.arg(0), tags: flags.values(TAG), level, grep };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let dst = match flags.has(SAVE) {
+        false => None,
+        true => Some(Template::new(&device, config).file(&config.logs.destination, "", &config.logs.name)),
+    };
+    return match view(&device, &filter, dst.as_ref()) {
+        Ok(()) => {
+            if let Some(dst) = dst {
+                SAVED.println_formatted(&[&dst.to_string_lossy()]);
+            }
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            e.eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn view(device: &AdbDevice, filter: &Filter, dst: Option<&PathBuf>) -> Result<(), String> {
+    let mut file = match dst {
+        None => None,
+        Some(dst) => {
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent).string_err()?;
+            }
+            Some(BufWriter::new(File::create(dst).string_err()?))
+        },
+    };
+    let mut processes = Processes { names: HashMap::new(), refreshed: None };
+    processes.refresh(device);
+    let mut pids = processes.names.iter()
+        .filter(|(_, name)| filter.of_package(name))
+        .map(|(pid, _)| *pid)
+        .collect::<Vec<u32>>();
+    if let (Some(package), true) = (&filter.package, pids.is_empty()) {
+        LOG_WAITING.println_formatted(&[package]);
+    }
+    let regex = Regex::new(THREADTIME).unwrap();
+    let started = Regex::new(PROC_START).unwrap();
+    let died = Regex::new(PROC_DIED).unwrap();
+    let mut child = adb_args_with(device, AdbArgs::spawn(LOGCAT_ARGS))
+        .command()?
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .string_err()?;
+    let stdout = child.stdout.take().unwrap();
+    let mut out = StandardStream::stdout(ColorChoice::Auto);
+    // Ctrl-C stops adb as well, the loop ends with its output
+    catch_interrupts();
+    let follow = || -> Result<(), String> {
+        for raw in BufReader::new(stdout).lines() {
+            let raw = match raw {
+                Ok(raw) => raw,
+                Err(_) => break,
+            };
+            if interrupted() {
+                break
+            }
+            if let Some(file) = file.as_mut() {
+                writeln!(file, "{raw}").string_err()?;
+            }
+            let line = match LogLine::parse(&raw, &regex) {
+                Some(line) => line,
+                None => continue,
+            };
+            if line.tag == ACTIVITY_MANAGER {
+                if let Some(captures) = started.captures(&line.message) {
+                    let (pid, name) = (captures[1].parse::<u32>().unwrap_or(0), &captures[2]);
+                    processes.names.insert(pid, name.to_string());
+                    if filter.package.is_some() && filter.of_package(name) {
+                        pids.push(pid);
+                        LOG_PROCESS_STARTED.println_formatted(&[name, &pid.to_string()]);
+                    }
+                } else if let Some(captures) = died.captures(&line.message) {
+                    let (name, pid) = (&captures[1], captures[2].parse::<u32>().unwrap_or(0));
+                    if filter.package.is_some() && pids.contains(&pid) {
+                        pids.retain(|it| *it != pid);
+                        LOG_PROCESS_DIED.println_formatted(&[name, &pid.to_string()]);
+                    }
+                }
+            }
+            let name = match (filter.package.is_some(), pids.contains(&line.pid)) {
+                (false, _) => processes.name(device, line.pid).cloned(),
+                (true, true) => None,
+                (true, false) => match processes.name(device, line.pid) {
+                    Some(name) if filter.of_package(name) => {
+                        pids.push(line.pid);
+                        None
+                    },
+                    _ => continue,
+                },
+            };
+            if filter.accepts(&line) {
+                print_line(&mut out, &line, name).string_err()?;
+            }
+        }
+        return Ok(())
+    };
+    let result = follow();
+    let _ = child.kill();
+    let _ = child.wait();
+    release_interrupts();
+    result?;
+    if let Some(mut file) = file {
+        file.flush().string_err()?;
+    }
+    return Ok(())
+}
+
+/// With a package the process column is left out, all the lines are of this package.
+fn print_line(out: &mut StandardStream, line: &LogLine, name: Option<String>) -> std::io::Result<()> {
+    let color = level_color(line.level);
+    write!(out, "{} ", line.time)?;
+    if let Some(name) = name {
+        let name = ellipsize(&name, NAME_WIDTH, true);
+        write!(out, "{name:>NAME_WIDTH$} ")?;
+    }
+    out.set_color(ColorSpec::new().set_fg(Some(Color::Black)).set_bg(color))?;
+    write!(out, " {} ", line.level)?;
+    out.reset()?;
+    out.set_color(ColorSpec::new().set_fg(color))?;
+    write!(out, " {:>TAG_WIDTH$} ", ellipsize(&line.tag, TAG_WIDTH, false))?;
+    writeln!(out, "{}", line.message)?;
+    return out.reset()
+}
+
+/// The main process or a `package:service` one.
+pub fn is_process_of(name: &str, package: &str) -> bool {
+    name == package || name.strip_prefix(package).is_some_and(|it| it.starts_with(':'))
+}
+
+fn level_index(level: char) -> Option<usize> {
+    LEVELS.find(level)
+}
+
+fn level_color(level: char) -> Option<Color> {
+    match level {
+        'D' => Some(Color::Blue),
+        'I' => Some(Color::Green),
+        'W' => Some(Color::Yellow),
+        'E' => Some(Color::Red),
+        'F' | 'A' => Some(Color::Magenta),
+        _ => Some(Color::White),
+    }
+}
+
+/// Keeps the end of the process names, the package prefix is the least telling part.
+fn ellipsize(value: &str, width: usize, keep_end: bool) -> String {
+    let count = value.chars().count();
+    if count <= width {
+        return value.to_string()
+    }
+    return match keep_end {
+        true => format!("…{}", value.chars().skip(count - width + 1).collect::<String>()),
+        false => format!("{}…", value.chars().take(width - 1).collect::<String>()),
+    }
+}
@@ -0,0 +1,465 @@
+use std::fs::File;
+use std::io;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Instant;
+
+const TIMESCALE: u32 = 90_000;
+const MOVIE_TIMESCALE: u32 = 1000;
+const DEFAULT_DELTA: u32 = TIMESCALE / 30;
+const NAL_SLICE: u8 = 1;
+const NAL_IDR: u8 = 5;
+const NAL_SEI: u8 = 6;
+const NAL_SPS: u8 = 7;
+const NAL_PPS: u8 = 8;
+const NAL_AUD: u8 = 9;
+const MATRIX: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+const HIGH_PROFILES: [u8; 13] = [100, 110, 122, 244, 44, 83, 86, 118, 128, 138, 139, 134, 135];
+
+/// Splits a raw H.264 Annex B stream into NAL units.
+pub struct AnnexB {
+    buf: Vec<u8>,
+}
+
+impl AnnexB {
+    pub fn new() -> AnnexB {
+        AnnexB { buf: vec![] }
+    }
+
+    /// Appends the bytes and returns the NAL units which are known to be complete.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let from = self.buf.len().saturating_sub(3);
+        self.buf.extend_from_slice(bytes);
+        let mut nals = vec![];
+        let first = match find_start_code(&self.buf, 0) {
+            Some(first) => first,
+            None => return nals,
+        };
+        let mut start = first.1;
+        let mut search = from.max(start);
+        while let Some((end, next)) = find_start_code(&self.buf, search) {
+            if end > start {
+                nals.push(self.buf[start..end].to_vec());
+            }
+            start = next;
+            search = next;
+        }
+        self.buf.drain(..start);
+        // keep the start code to find the end of the pending unit on the next push
+        self.buf.splice(0..0, [0, 0, 1]);
+        return nals
+    }
+}
+
+/// Returns the range of the first start code from the offset, trailing zero bytes included.
+fn find_start_code(buf: &[u8], from: usize) -> Option<(usize, usize)> {
+    let mut i = from;
+    while i + 3 <= buf.len() {
+        if buf[i] == 0 && buf[i + 1] == 0 && buf[i + 2] == 1 {
+            let mut start = i;
+            while start > from && buf[start - 1] == 0 {
+                start -= 1;
+            }
+            return Some((start, i + 3));
+        }
+        i += 1;
+    }
+    return None
+}
+
+struct Sample {
+    offset: u64,
+    size: u32,
+    time: u64,
+    key: bool,
+}
+
+/// Writes an H.264 elementary stream into an MP4 file sample by sample,
+/// the index is appended on [Mp4Writer::finish].
+pub struct Mp4Writer {
+    file: File,
+    sps: Option<Vec<u8>>,
+    pps: Option<Vec<u8>>,
+    samples: Vec<Sample>,
+    pending: Vec<u8>,
+    pending_time: Option<Instant>,
+    pending_key: bool,
+    pending_vcl: bool,
+    start: Option<Instant>,
+    mdat_start: u64,
+    offset: u64,
+}
+
+impl Mp4Writer {
+    pub fn create(path: &Path) -> io::Result<Mp4Writer> {
+        let mut file = File::create(path)?;
+        let ftyp = mp4_box(b"ftyp", [b"isom".as_slice(), &512u32.to_be_bytes(), b"isomiso2avc1mp41"].concat());
+        file.write_all(&ftyp)?;
+        let mdat_start = ftyp.len() as u64;
+        // the 64-bit size is patched when the recording is finished
+        file.write_all(&[0, 0, 0, 1])?;
+        file.write_all(b"mdat")?;
+        file.write_all(&[0; 8])?;
+        let offset = mdat_start + 16;
+        return Ok(Mp4Writer {
+            file,
+            sps: None,
+            pps: None,
+            samples: vec![],
+            pending: vec![],
+            pending_time: None,
+            pending_key: false,
+            pending_vcl: false,
+            start: None,
+            mdat_start,
+            offset,
+        })
+    }
+
+    pub fn push_nal(&mut self, nal: &[u8], at: Instant) -> io::Result<()> {
+        let kind = match nal.first() {
+            Some(header) => header & 0x1F,
+            None => return Ok(()),
+        };
+        let vcl = kind == NAL_SLICE || kind == NAL_IDR;
+        // the first_mb_in_slice is ue(v), so the first bit is set for the zero value
+        let first_slice = vcl && nal.get(1).map(|it| it & 0x80 != 0).unwrap_or(false);
+        let new_access_unit = first_slice || (NAL_SEI..=NAL_AUD).contains(&kind);
+        if new_access_unit && self.pending_vcl {
+            self.write_pending()?;
+        }
+        match kind {
+            NAL_SPS => if self.sps.is_none() { self.sps = Some(nal.to_vec()) },
+            NAL_PPS => if self.pps.is_none() { self.pps = Some(nal.to_vec()) },
+            NAL_AUD => (),
+            _ => {
+                if self.pending_time.is_none() {
+                    self.pending_time = Some(at);
+                }
+                self.pending.extend_from_slice(&(nal.len() as u32).to_be_bytes());
+                self.pending.extend_from_slice(nal);
+                self.pending_key |= kind == NAL_IDR;
+                self.pending_vcl |= vcl;
+            },
+        }
+        return Ok(())
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let at = self.pending_time.take().unwrap_or_else(Instant::now);
+        let start = *self.start.get_or_insert(at);
+        let time = at.duration_since(start).as_micros() as u64 * TIMESCALE as u64 / 1_000_000;
+        self.file.write_all(&self.pending)?;
+        self.samples.push(Sample {
+            offset: self.offset,
+            size: self.pending.len() as u32,
+            time,
+            key: self.pending_key,
+        });
+        self.offset += self.pending.len() as u64;
+        self.pending.clear();
+        self.pending_key = false;
+        self.pending_vcl = false;
+        return Ok(())
+    }
+
+    /// Drops the pending access unit, it may be truncated, and writes the index.
+    pub fn finish(mut self) -> io::Result<()> {
+        let (sps, pps) = match (&self.sps, &self.pps) {
+            (Some(sps), Some(pps)) if !self.samples.is_empty() => (sps.clone(), pps.clone()),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "no video data received")),
+        };
+        // a cut-off stream can leave a truncated SPS, the avcC box needs its profile and level bytes
+        let dimensions = match sps_dimensions(&sps) {
+            Some((width, height)) if sps.len() >= 4 && (1..=0xFFFF).contains(&width) && (1..=0xFFFF).contains(&height) => (width, height),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid sequence parameter set")),
+        };
+        let mdat_size = self.offset - self.mdat_start;
+        self.file.seek(SeekFrom::Start(self.mdat_start + 8))?;
+        self.file.write_all(&mdat_size.to_be_bytes())?;
+        self.file.seek(SeekFrom::Start(self.offset))?;
+        let moov = self.moov(&sps, &pps, dimensions);
+        self.file.write_all(&moov)?;
+        self.file.set_len(self.offset + moov.len() as u64)?;
+        return self.file.flush()
+    }
+
+    fn deltas(&self) -> Vec<u32> {
+        let mut deltas = self.samples.windows(2)
+            .map(|pair| (pair[1].time.saturating_sub(pair[0].time) as u32).max(1))
+            .collect::<Vec<u32>>();
+        deltas.push(deltas.last().cloned().unwrap_or(DEFAULT_DELTA));
+        return deltas
+    }
+
+    fn moov(&self, sps: &[u8], pps: &[u8], (width, height): (u32, u32)) -> Vec<u8> {
+        let deltas = self.deltas();
+        let duration = deltas.iter().map(|it| *it as u64).sum::<u64>();
+        let movie_duration = (duration * MOVIE_TIMESCALE as u64 / TIMESCALE as u64) as u32;
+
+        let mut mvhd = vec![];
+        mvhd.put_u32(0).put_u32(0).put_u32(MOVIE_TIMESCALE).put_u32(movie_duration)
+            .put_u32(0x0001_0000).put_u16(0x0100).put_zeros(10).put_matrix()
+            .put_zeros(24).put_u32(2);
+
+        let mut tkhd = vec![];
+        tkhd.put_u32(0).put_u32(0).put_u32(1).put_u32(0).put_u32(movie_duration)
+            .put_zeros(8).put_u16(0).put_u16(0).put_u16(0).put_u16(0).put_matrix()
+            .put_u32(width << 16).put_u32(height << 16);
+
+        let mut mdhd = vec![];
+        mdhd.put_u32(0).put_u32(0).put_u32(TIMESCALE).put_u32(duration as u32)
+            .put_u16(0x55C4).put_u16(0); // und
+
+        let mut hdlr = vec![];
+        hdlr.put_u32(0).put_bytes(b"vide").put_zeros(12).put_bytes(b"VideoHandler\0");
+
+        let mut url = vec![];
+        url.put_u32(1);
+        let mut dref = vec![];
+        dref.put_u32(0).put_u32(1).put_bytes(&mp4_box(b"url ", url));
+
+        let mut avcc = vec![];
+        avcc.put_bytes(&[1, sps[1], sps[2], sps[3], 0xFF, 0xE1])
+            .put_u16(sps.len() as u16).put_bytes(sps)
+            .put_bytes(&[1]).put_u16(pps.len() as u16).put_bytes(pps);
+        let mut avc1 = vec![];
+        avc1.put_zeros(6).put_u16(1).put_zeros(16)
+            .put_u16(width as u16).put_u16(height as u16)
+            .put_u32(0x0048_0000).put_u32(0x0048_0000).put_u32(0).put_u16(1)
+            .put_zeros(32).put_u16(0x0018).put_u16(0xFFFF)
+            .put_bytes(&mp4_box(b"avcC", avcc));
+        let mut stsd = vec![];
+        stsd.put_u32(0).put_u32(1).put_bytes(&mp4_box(b"avc1", avc1));
+
+        let mut runs: Vec<(u32, u32)> = vec![];
+        for delta in deltas {
+            match runs.last_mut() {
+                Some((count, last)) if *last == delta => *count += 1,
+                _ => runs.push((1, delta)),
+            }
+        }
+        let mut stts = vec![];
+        stts.put_u32(0).put_u32(runs.len() as u32);
+        for (count, delta) in runs {
+            stts.put_u32(count).put_u32(delta);
+        }
+        let keys = self.samples.iter()
+            .enumerate()
+            .filter(|(_, it)| it.key)
+            .map(|(i, _)| i as u32 + 1)
+            .collect::<Vec<u32>>();
+        let mut stss = vec![];
+        stss.put_u32(0).put_u32(keys.len() as u32);
+        keys.iter().for_each(|it| { stss.put_u32(*it); });
+        let mut stsz = vec![];
+        stsz.put_u32(0).put_u32(0).put_u32(self.samples.len() as u32);
+        self.samples.iter().for_each(|it| { stsz.put_u32(it.size); });
+        let mut stsc = vec![];
+        stsc.put_u32(0).put_u32(1).put_u32(1).put_u32(1).put_u32(1);
+        let mut co64 = vec![];
+        co64.put_u32(0).put_u32(self.samples.len() as u32);
+        self.samples.iter().for_each(|it| { co64.put_u64(it.offset); });
+
+        let mut stbl = [
+            mp4_box(b"stsd", stsd),
+            mp4_box(b"stts", stts),
+        ].concat();
+        if keys.len() < self.samples.len() {
+            stbl.extend(mp4_box(b"stss", stss));
+        }
+        stbl.extend([mp4_box(b"stsz", stsz), mp4_box(b"stsc", stsc), mp4_box(b"co64", co64)].concat());
+
+        let mut vmhd = vec![];
+        vmhd.put_u32(1).put_zeros(8);
+        let minf = [
+            mp4_box(b"vmhd", vmhd),
+            mp4_box(b"dinf", mp4_box(b"dref", dref)),
+            mp4_box(b"stbl", stbl),
+        ].concat();
+        let mdia = [
+            mp4_box(b"mdhd", mdhd),
+            mp4_box(b"hdlr", hdlr),
+            mp4_box(b"minf", minf),
+        ].concat();
+        let trak = [
+            mp4_box(b"tkhd", [&[0, 0, 0, 3], tkhd.as_slice()].concat()),
+            mp4_box(b"mdia", mdia),
+        ].concat();
+        return mp4_box(b"moov", [mp4_box(b"mvhd", mvhd), mp4_box(b"trak", trak)].concat())
+    }
+}
+
+fn mp4_box(kind: &[u8; 4], payload: Vec<u8>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 8);
+    data.put_u32(payload.len() as u32 + 8).put_bytes(kind).put_bytes(&payload);
+    return data
+}
+
+trait Put {
+    fn put_bytes(&mut self, bytes: &[u8]) -> &mut Self;
+    fn put_u16(&mut self, value: u16) -> &mut Self;
+    fn put_u32(&mut self, value: u32) -> &mut Self;
+    fn put_u64(&mut self, value: u64) -> &mut Self;
+    fn put_zeros(&mut self, count: usize) -> &mut Self;
+    fn put_matrix(&mut self) -> &mut Self;
+}
+
+impl Put for Vec<u8> {
+    fn put_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.extend_from_slice(bytes);
+        self
+    }
+    fn put_u16(&mut self, value: u16) -> &mut Self {
+        self.put_bytes(&value.to_be_bytes())
+    }
+    fn put_u32(&mut self, value: u32) -> &mut Self {
+        self.put_bytes(&value.to_be_bytes())
+    }
+    fn put_u64(&mut self, value: u64) -> &mut Self {
+        self.put_bytes(&value.to_be_bytes())
+    }
+    fn put_zeros(&mut self, count: usize) -> &mut Self {
+        self.resize(self.len() + count, 0);
+        self
+    }
+    fn put_matrix(&mut self) -> &mut Self {
+        MATRIX.iter().for_each(|it| { self.put_u32(*it); });
+        self
+    }
+}
+
+struct BitReader {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl BitReader {
+    fn new(nal: &[u8]) -> BitReader {
+        // drop the emulation prevention bytes
+        let mut data = Vec::with_capacity(nal.len());
+        let mut zeros = 0;
+        for &byte in nal {
+            if zeros >= 2 && byte == 3 {
+                zeros = 0;
+                continue
+            }
+            zeros = if byte == 0 { zeros + 1 } else { 0 };
+            data.push(byte);
+        }
+        BitReader { data, pos: 0 }
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        let byte = self.data.get(self.pos / 8)?;
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        return Some(bit as u32)
+    }
+
+    fn bits(&mut self, count: usize) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | self.bit()?;
+        }
+        return Some(value)
+    }
+
+    fn ue(&mut self) -> Option<u32> {
+        let mut zeros = 0;
+        while self.bit()? == 0 {
+            zeros += 1;
+            if zeros > 31 {
+                return None
+            }
+        }
+        return Some((1u32 << zeros) - 1 + self.bits(zeros)?)
+    }
+
+    fn se(&mut self) -> Option<i32> {
+        let value = self.ue()?;
+        return Some(if value % 2 == 0 { -((value / 2) as i32) } else { (value / 2 + 1) as i32 })
+    }
+}
+
+/// Returns the picture size declared by the sequence parameter set.
+pub fn sps_dimensions(sps: &[u8]) -> Option<(u32, u32)> {
+    let mut reader = BitReader::new(sps);
+    reader.bits(8)?; // the NAL header
+    let profile = reader.bits(8)? as u8;
+    reader.bits(16)?; // constraints and level
+    reader.ue()?; // seq_parameter_set_id
+    let mut chroma_format = 1;
+    let mut separate_planes = 0;
+    if HIGH_PROFILES.contains(&profile) {
+        chroma_format = reader.ue()?;
+        if chroma_format == 3 {
+            separate_planes = reader.bit()?;
+        }
+        reader.ue()?; // bit_depth_luma_minus8
+        reader.ue()?; // bit_depth_chroma_minus8
+        reader.bit()?; // qpprime_y_zero_transform_bypass_flag
+        if reader.bit()? == 1 {
+            let lists = if chroma_format == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if reader.bit()? == 1 {
+                    skip_scaling_list(&mut reader, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+    reader.ue()?; // log2_max_frame_num_minus4
+    match reader.ue()? {
+        0 => { reader.ue()?; },
+        1 => {
+            reader.bit()?;
+            reader.se()?;
+            reader.se()?;
+            for _ in 0..reader.ue()? {
+                reader.se()?;
+            }
+        },
+        _ => (),
+    }
+    reader.ue()?; // max_num_ref_frames
+    reader.bit()?; // gaps_in_frame_num_value_allowed_flag
+    let width_in_mbs = reader.ue()? + 1;
+    let height_in_map_units = reader.ue()? + 1;
+    let frame_mbs_only = reader.bit()?;
+    if frame_mbs_only == 0 {
+        reader.bit()?; // mb_adaptive_frame_field_flag
+    }
+    reader.bit()?; // direct_8x8_inference_flag
+    let (mut left, mut right, mut top, mut bottom) = (0, 0, 0, 0);
+    if reader.bit()? == 1 {
+        left = reader.ue()?;
+        right = reader.ue()?;
+        top = reader.ue()?;
+        bottom = reader.ue()?;
+    }
+    let (crop_x, crop_y) = match chroma_format {
+        _ if separate_planes == 1 => (1, 2 - frame_mbs_only),
+        0 => (1, 2 - frame_mbs_only),
+        1 => (2, 2 * (2 - frame_mbs_only)),
+        2 => (2, 2 - frame_mbs_only),
+        _ => (1, 2 - frame_mbs_only),
+    };
+    // the crop of a broken SPS can be larger than the frame
+    let width = width_in_mbs.checked_mul(16)?
+        .checked_sub(left.checked_add(right)?.checked_mul(crop_x)?)?;
+    let height = (2 - frame_mbs_only).checked_mul(height_in_map_units)?.checked_mul(16)?
+        .checked_sub(top.checked_add(bottom)?.checked_mul(crop_y)?)?;
+    return Some((width, height))
+}
+
+fn skip_scaling_list(reader: &mut BitReader, size: usize) -> Option<()> {
+    let mut last = 8;
+    let mut next = 8;
+    for _ in 0..size {
+        if next != 0 {
+            next = (last + reader.se()? + 256) % 256;
+        }
+        last = if next == 0 { last } else { next };
+    }
+    return Some(())
+}
@@ -0,0 +1,214 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::ext::{OutputExt, PrintExt, StringExt};
+use crate::core::flags::Flags;
+use crate::core::r#const::SHELL;
+use crate::core::selector::{fetch_adb_devices, resolve_device, run_adb_with};
+use crate::core::strings::{NO_PACKAGES, SELECT_PACKAGE};
+use crate::core::util::interactive_select;
+use std::collections::BTreeMap;
+use std::process::{ExitCode, Output};
+use std::time::{Duration, Instant};
+
+const LIST_PACKAGES: &str = "pm list packages -f -U --show-versioncode";
+const DUMP_FLAGS: &str = "dumpsys package packages | grep -E '^ *(Package \\[|pkgFlags=)'";
+const PACKAGE_PREFIX: &str = "package:";
+const VERSION_CODE: &str = " versionCode:";
+const UID: &str = " uid:";
+const DEBUGGABLE: &str = "DEBUGGABLE";
+const THIRD_PARTY: &str = "third-party";
+const SYSTEM: &str = "system";
+const DISABLED: &str = "disabled";
+const DEBUGGABLE_FLAG: &str = "debuggable";
+
+const DEVICES_REFRESH: Duration = Duration::from_secs(5);
+
+// serial: the package names for the REPL completion
+static mut PACKAGE_CACHE: BTreeMap<String, Vec<String>> = BTreeMap::new();
+// when the devices were listed and the serial if there was a single one
+static mut COMPLETION_DEVICE: Option<(Instant, Option<String>)> = None;
+
+pub struct Package {
+    pub name: String,
+    pub path: String,
+    pub version_code: Option<String>,
+    pub uid: Option<String>,
+}
+
+#[derive(Default)]
+pub struct PackageFilter {
+    pub third_party: bool,
+    pub system: bool,
+    pub disabled: bool,
+    pub debuggable: bool,
+    pub substring: Option<String>,
+}
+
+impl PackageFilter {
+    pub fn third_party() -> PackageFilter {
+        PackageFilter { third_party: true, ..PackageFilter::default() }
+    }
+
+    fn pm_flags(&self) -> String {
+        let flags = [(self.third_party, "-3"), (self.system, "-s"), (self.disabled, "-d")];
+        return flags.iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, flag)| *flag)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The `packages` command.
+pub fn packages(args: &[String]) -> ExitCode {
+    let switches = [THIRD_PARTY, SYSTEM, DISABLED, DEBUGGABLE_FLAG];
+    let flags = match Flags::parse(args, &switches, &[]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let filter = PackageFilter {
+        third_party: flags.has(THIRD_PARTY),
+        system: flags.has(SYSTEM),
+        disabled: flags.has(DISABLED),
+        debuggable: flags.has(DEBUGGABLE_FLAG),
+        substring: flags.arg(0),
+    };
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let packages = match list_packages(&device, &filter) {
+        Ok(packages) => packages,
+        Err(output) => {
+            output.print_err();
+            return output.exit_code()
+        }
+    };
+    let width = packages.iter().map(|it| it.name.len()).max().unwrap_or(0);
+    for package in packages {
+        let version_code = package.version_code.unwrap_or_default();
+        let uid = package.uid.unwrap_or_default();
+        format!("{:width$}  {version_code:>10}  {uid:>6}  {}", package.name, package.path).println();
+    }
+    return ExitCode::SUCCESS
+}
+
+pub fn list_packages(device: &AdbDevice, filter: &PackageFilter) -> Result<Vec<Package>, Output> {
+    let command = format!("{LIST_PACKAGES} {}", filter.pm_flags());
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.trim_end()]));
+    if !output.status.success() {
+        return Err(output)
+    }
+    let mut packages = output.stdout()
+        .lines()
+        .filter_map(parse_package)
+        .collect::<Vec<_>>();
+    packages.sort_by(|first, second| first.name.cmp(&second.name));
+    if filter.pm_flags().is_empty() {
+        let names = packages.iter().map(|it| it.name.clone()).collect();
+        unsafe { (*&raw mut PACKAGE_CACHE).insert(device.serial.clone(), names) };
+    }
+    if let Some(substring) = &filter.substring {
+        packages.retain(|it| it.name.contains_ci(substring));
+    }
+    if filter.debuggable {
+        let debuggable = debuggable_packages(device);
+        packages.retain(|it| debuggable.contains(&it.name));
+    }
+    return Ok(packages)
+}
+
+/// "package:/data/app/~~Xw==/app.package.name-Yw==/base.apk=app.package.name versionCode:42 uid:10234"
+fn parse_package(line: &str) -> Option<Package> {
+    let mut rest = line.trim().strip_prefix(PACKAGE_PREFIX)?;
+    let mut take = |key: &str| -> Option<String> {
+        let (head, value) = rest.rsplit_once(key)?;
+        rest = head;
+        return Some(value.trim().to_string())
+    };
+    let uid = take(UID);
+    let version_code = take(VERSION_CODE);
+    let (path, name) = rest.rsplit_once('=')?;
+    return Some(Package { name: name.to_string(), path: path.to_string(), version_code, uid })
+}
+
+fn debuggable_packages(device: &AdbDevice) -> Vec<String> {
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, DUMP_FLAGS])).stdout();
+    let mut packages = vec![];
+    let mut current = None;
+    for line in output.lines().map(str::trim) {
+        match line.strip_prefix("Package [") {
+            Some(rest) => current = rest.split_once(']').map(|(name, _)| name.to_string()),
+            None if line.contains(DEBUGGABLE) => packages.extend(current.take()),
+            None => {},
+        }
+    }
+    return packages
+}
+
+/// Opens the fuzzy picker over the packages of the filter.
+pub fn pick_package(device: &AdbDevice, filter: &PackageFilter) -> Result<String, ExitCode> {
+    let packages = match list_packages(device, filter) {
+        Ok(packages) => packages,
+        Err(output) => {
+            output.print_err();
+            return Err(output.exit_code())
+        }
+    };
+    if packages.is_empty() {
+        NO_PACKAGES.eprintln();
+        return Err(ExitCode::FAILURE)
+    }
+    return interactive_select(SELECT_PACKAGE.value(), packages, |it, _| match &it.version_code {
+        Some(version_code) => format!("{} ({version_code})", it.name),
+        None => it.name.clone(),
+    }).map(|it| it.name)
+}
+
+/// The passed package or the picked one.
+pub fn package_or_pick(device: &AdbDevice, package: Option<String>, filter: &PackageFilter) -> Result<String, ExitCode> {
+    return match package {
+        Some(package) => Ok(package),
+        None => pick_package(device, filter),
+    }
+}
+
+/// For the REPL completion, fetched once per device if there is a single one.
+pub fn cached_package_names() -> Vec<String> {
+    let serial = match completion_serial() {
+        Some(serial) => serial,
+        None => return vec![],
+    };
+    if let Some(names) = unsafe { (*&raw const PACKAGE_CACHE).get(&serial).cloned() } {
+        return names
+    }
+    return match fetch_adb_devices().into_iter().find(|it| it.ok && it.serial == serial) {
+        Some(device) => list_packages(&device, &PackageFilter::default())
+            .map(|it| it.into_iter().map(|it| it.name).collect())
+            .unwrap_or_default(),
+        None => vec![],
+    }
+}
+
+/// The names are listed again on the next completion.
+pub fn forget_packages() {
+    unsafe { (*&raw mut PACKAGE_CACHE).clear() }
+}
+
+/// The devices are listed at most once in `DEVICES_REFRESH`, not on every Tab.
+fn completion_serial() -> Option<String> {
+    if let Some((listed, serial)) = unsafe { (*&raw const COMPLETION_DEVICE).clone() } {
+        if listed.elapsed() < DEVICES_REFRESH {
+            return serial
+        }
+    }
+    let serial = match fetch_adb_devices().as_slice() {
+        [device] if device.ok => Some(device.serial.clone()),
+        _ => None,
+    };
+    unsafe { COMPLETION_DEVICE = Some((Instant::now(), serial.clone())) }
+    return serial
+}
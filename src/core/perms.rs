@@ -0,0 +1,180 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::packages::{package_or_pick, PackageFilter};
+use crate::core::r#const::SHELL;
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::{NO_PERMISSIONS, PERMISSIONS_RESET, PERMISSION_GRANTED, PERMISSION_REVOKED, SELECT_PERMISSION, USAGE};
+use crate::core::util::interactive_select;
+use regex::Regex;
+use std::process::ExitCode;
+
+const GRANT: &str = "grant";
+const REVOKE: &str = "revoke";
+const RESET: &str = "reset";
+const USAGE_TEXT: &str = "perms [app.package.name] [grant|revoke [permission…]|reset]";
+const INSTALL_PERMISSIONS: &str = "install permissions:";
+const RUNTIME_PERMISSIONS: &str = "runtime permissions:";
+// android.permission.CAMERA: granted=false, flags=[ USER_SET|USER_FIXED ]
+const PERMISSION: &str = r"^([\w.]+): granted=(true|false)(?:, flags=\[\s*([^\]]*?)\s*\])?";
+const USER_SET: &str = "USER_SET";
+const USER_FIXED: &str = "USER_FIXED";
+const PERMISSION_PREFIX: &str = "android.permission.";
+
+pub struct Permission {
+    pub name: String,
+    pub granted: bool,
+    /// only these can be granted and revoked
+    pub runtime: bool,
+    pub flags: Vec<String>,
+}
+
+/// The `perms` command.
+pub fn perms(args: &[String]) -> ExitCode {
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let package = match package_or_pick(&device, args.first().cloned(), &PackageFilter::default()) {
+        Ok(package) => package,
+        Err(code) => return code,
+    };
+    let permissions = permissions(&device, &package);
+    let names = args.iter().skip(2).map(|it| full_name(it)).collect::<Vec<_>>();
+    return match args.get(1).map(String::as_str) {
+        None => {
+            print_permissions(&permissions);
+            ExitCode::SUCCESS
+        },
+        Some(GRANT) => change(&device, &package, &permissions, names, true),
+        Some(REVOKE) => change(&device, &package, &permissions, names, false),
+        Some(RESET) => reset(&device, &package, &permissions),
+        Some(_) => {
+            USAGE.formatted(&[USAGE_TEXT]).eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// The install and runtime permissions of the first user in the dump.
+pub fn permissions(device: &AdbDevice, package: &str) -> Vec<Permission> {
+    let command = format!("dumpsys package {package}");
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()])).stdout();
+    return parse_permissions(&output)
+}
+
+pub fn parse_permissions(dump: &str) -> Vec<Permission> {
+    let pattern = Regex::new(PERMISSION).unwrap();
+    let mut permissions: Vec<Permission> = vec![];
+    let mut runtime = None;
+    for line in dump.lines().map(str::trim) {
+        match line {
+            INSTALL_PERMISSIONS => runtime = Some(false),
+            RUNTIME_PERMISSIONS => runtime = Some(true),
+            _ if line.ends_with("permissions:") => runtime = None,
+            _ => {},
+        }
+        let (runtime, captures) = match (runtime, pattern.captures(line)) {
+            (Some(runtime), Some(captures)) => (runtime, captures),
+            _ => continue,
+        };
+        if permissions.iter().any(|it| it.name == captures[1]) {
+            continue
+        }
+        permissions.push(Permission {
+            name: captures[1].to_string(),
+            granted: &captures[2] == "true",
+            runtime,
+            flags: captures.get(3)
+                .map(|it| it.as_str().split('|').map(String::from).filter(|it| !it.is_empty()).collect())
+                .unwrap_or_default(),
+        });
+    }
+    return permissions
+}
+
+fn print_permissions(permissions: &[Permission]) {
+    if permissions.is_empty() {
+        NO_PERMISSIONS.eprintln();
+    }
+    for permission in permissions {
+        let mark = match permission.granted {
+            true => "✔",
+            false => "✘",
+        };
+        let flags = match permission.flags.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", permission.flags.join("|")),
+        };
+        format!("{mark} {}{flags}", permission.name).println();
+    }
+}
+
+/// Grants or revokes the passed runtime permissions or the picked one.
+fn change(device: &AdbDevice, package: &str, permissions: &[Permission], names: Vec<String>, grant: bool) -> ExitCode {
+    let names = match names.is_empty() {
+        false => names,
+        true => {
+            let candidates = permissions.iter()
+                .filter(|it| it.runtime && it.granted != grant)
+                .map(|it| it.name.clone())
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                NO_PERMISSIONS.eprintln();
+                return ExitCode::FAILURE
+            }
+            match interactive_select(SELECT_PERMISSION.value(), candidates, |it, _| it.clone()) {
+                Ok(name) => vec![name],
+                Err(code) => return code,
+            }
+        },
+    };
+    return apply(device, package, names, grant)
+}
+
+/// Grants or revokes the permissions one by one, so a failed one doesn't stop the rest.
+pub fn apply(device: &AdbDevice, package: &str, names: Vec<String>, grant: bool) -> ExitCode {
+    let (pm, done) = match grant {
+        true => ("pm grant", &PERMISSION_GRANTED),
+        false => ("pm revoke", &PERMISSION_REVOKED),
+    };
+    let mut code = ExitCode::SUCCESS;
+    for name in names {
+        let command = format!("{pm} {package} {name}");
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+        match output.status.success() {
+            true => done.println_formatted(&[&name]),
+            false => {
+                output.print_out_and_err();
+                code = output.exit_code();
+            },
+        }
+    }
+    return code
+}
+
+/// Clears the flags that make the system stop asking for the permissions.
+fn reset(device: &AdbDevice, package: &str, permissions: &[Permission]) -> ExitCode {
+    let commands = permissions.iter()
+        .filter(|it| it.runtime && it.flags.iter().any(|it| it == USER_SET || it == USER_FIXED))
+        .map(|it| format!("pm clear-permission-flags {package} {} user-set user-fixed", it.name))
+        .collect::<Vec<_>>();
+    if !commands.is_empty() {
+        let command = commands.join(" && ");
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+        if !output.status.success() {
+            output.print_out_and_err();
+            return output.exit_code()
+        }
+    }
+    PERMISSIONS_RESET.println_formatted(&[&commands.len().to_string(), package]);
+    return ExitCode::SUCCESS
+}
+
+/// `CAMERA` is `android.permission.CAMERA`.
+fn full_name(name: &str) -> String {
+    match name.contains('.') {
+        true => name.to_string(),
+        false => format!("{PERMISSION_PREFIX}{name}"),
+    }
+}
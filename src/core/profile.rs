@@ -0,0 +1,179 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::{Config, Profile};
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::r#const::{NULL, SHELL};
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::*;
+use crate::core::toggles::CALL;
+use std::process::ExitCode;
+
+const SAVE: &str = "save";
+const APPLY: &str = "apply";
+const RESTORE: &str = "restore";
+const USAGE_TEXT: &str = "profile save name | profile apply name | profile restore";
+// an unset property is off
+const PROP_UNSET: &str = "false";
+// printed with the key of a setting which failed to be set
+const FAILED_PREFIX: &str = "failed:";
+
+#[derive(Clone, Copy)]
+enum Source {
+    System,
+    Global,
+    Prop,
+}
+
+/// Everything the toggles and the rotation commands change.
+const SETTINGS: &[(&str, Source)] = &[
+    ("show_touches", Source::System),
+    ("pointer_location", Source::System),
+    ("debug.layout", Source::Prop),
+    ("window_animation_scale", Source::Global),
+    ("transition_animation_scale", Source::Global),
+    ("animator_duration_scale", Source::Global),
+    ("accelerometer_rotation", Source::System),
+    ("user_rotation", Source::System),
+];
+
+impl Source {
+    fn get(&self, key: &str) -> String {
+        match self {
+            Source::System => format!("settings get system {key}"),
+            Source::Global => format!("settings get global {key}"),
+            Source::Prop => format!("getprop {key}"),
+        }
+    }
+
+    fn put(&self, key: &str, value: &str) -> String {
+        match (self, value) {
+            (Source::System, NULL) => format!("settings delete system {key}"),
+            (Source::Global, NULL) => format!("settings delete global {key}"),
+            (Source::System, _) => format!("settings put system {key} {value}"),
+            (Source::Global, _) => format!("settings put global {key} {value}"),
+            (Source::Prop, _) => format!("setprop {key} {value}"),
+        }
+    }
+}
+
+/// The `profile save|apply|restore` command.
+pub fn profile(args: &[String], config: &mut Config) -> ExitCode {
+    let (action, name) = match (args.first().map(String::as_str), args.get(1)) {
+        (Some(action @ (SAVE | APPLY)), Some(name)) => (action, Some(name.clone())),
+        (Some(RESTORE), None) => (RESTORE, None),
+        _ => {
+            USAGE.formatted(&[USAGE_TEXT]).eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    if let (APPLY, Some(name)) = (action, &name) {
+        if !config.profiles.contains_key(name) {
+            no_profile(name, config);
+            return ExitCode::FAILURE
+        }
+    }
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let result = match (action, name) {
+        (SAVE, Some(name)) => current(&device).map(|current| {
+            config.profiles.insert(name.clone(), current);
+            PROFILE_SAVED.formatted(&[&name])
+        }),
+        (APPLY, Some(name)) => current(&device).and_then(|current| {
+            // applying another profile keeps the values from before the first one
+            config.profile_backups.entry(device.serial.clone()).or_insert(current);
+            apply(&device, &config.profiles[&name]).map(|_| PROFILE_APPLIED.formatted(&[&name]))
+        }),
+        _ => match config.profile_backups.remove(&device.serial) {
+            None => Err(NO_PROFILE_BACKUP.value().to_string()),
+            Some(backup) => apply(&device, &backup)
+                .map(|_| PROFILE_RESTORED.value().to_string())
+                .inspect_err(|_| {
+                    config.profile_backups.insert(device.serial.clone(), backup);
+                }),
+        },
+    };
+    if let Err(e) = config.write() {
+        e.eprintln();
+    }
+    return match result {
+        Ok(message) => {
+            message.println();
+            ExitCode::SUCCESS
+        },
+        Err(e) => {
+            e.eprintln();
+            ExitCode::FAILURE
+        },
+    }
+}
+
+pub fn no_profile(name: &str, config: &Config) {
+    let names = config.profiles.keys().cloned().collect::<Vec<_>>().join(", ");
+    NO_PROFILE.formatted(&[name, &names]).eprintln();
+}
+
+/// The current values of all the known settings, read at once, each line is `key=value`.
+pub fn current(device: &AdbDevice) -> Result<Profile, String> {
+    let command = SETTINGS.iter()
+        .map(|(key, source)| format!("if value=$({}); then echo \"{key}=$value\"; fi", source.get(key)))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+    if !output.status.success() {
+        return Err(output.stderr())
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let values = stdout.lines()
+        .filter_map(|it| it.trim().split_once('='))
+        .collect::<Vec<_>>();
+    let mut profile = Profile::new();
+    let mut unread = vec![];
+    for (key, source) in SETTINGS {
+        let value = match (source, values.iter().find(|(it, _)| it == key)) {
+            (Source::Prop, Some((_, ""))) => PROP_UNSET,
+            (_, Some((_, value))) => value,
+            (_, None) => {
+                unread.push(*key);
+                continue
+            },
+        };
+        profile.insert(key.to_string(), value.to_string());
+    }
+    return match unread.is_empty() {
+        true => Ok(profile),
+        false => Err(SETTINGS_NOT_READ.formatted(&[&unread.join(", ")])),
+    }
+}
+
+/// Sets the values of the profile in one shell, the apps are told about the changed property.
+pub fn apply(device: &AdbDevice, profile: &Profile) -> Result<(), String> {
+    let mut commands = vec![];
+    let mut props = false;
+    for (key, value) in profile {
+        let source = match SETTINGS.iter().find(|(it, _)| it == key) {
+            Some((_, source)) => *source,
+            None => return Err(UNKNOWN_SETTING.formatted(&[key])),
+        };
+        props |= matches!(source, Source::Prop);
+        commands.push(format!("{} || echo {FAILED_PREFIX}{key}", source.put(key, value)));
+    }
+    if props {
+        commands.push(format!("{CALL} || echo {FAILED_PREFIX}{CALL}"));
+    }
+    let output = run_adb_with(device, AdbArgs::run(&[SHELL, commands.join("; ").as_str()]));
+    if !output.status.success() {
+        return Err(output.stderr())
+    }
+    let failed = output.stdout()
+        .lines()
+        .filter_map(|it| it.trim().strip_prefix(FAILED_PREFIX))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    return match failed.is_empty() {
+        true => Ok(()),
+        false => Err(SETTINGS_NOT_SET.formatted(&[&failed.join(", ")])),
+    }
+}
@@ -1,16 +1,17 @@
 use crate::core::adb_command::AdbArgs;
 use crate::core::config::Config;
-use crate::core::destination::Destination;
 use crate::core::ext::{OutputExt, PathBufExt, PrintExt, ResultToOption, StrExt, VecExt};
 use crate::core::r#const::{PULL, SHELL};
 use crate::core::selector::{resolve_device, run_adb_with};
-use crate::core::strings::{ADD_INTERPRETER, MEDIAS_NOT_FOUND, SAVED};
+use crate::core::hook::{HookContext, HookSet};
+use crate::core::template::{numbered, Template};
+use crate::core::strings::{MEDIAS_NOT_FOUND, SAVED};
 use crate::core::util::{ensure_parent_exists, null, string};
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, ExitCode};
-use std::{fs, io};
+use std::fs;
+use std::process::ExitCode;
 
 const TOYBOX_LS_LLCD: &str = "toybox ls -llcd";
 const PICS: &[&str; 3] = &[".png", ".jpg", ".jpeg"];
@@ -20,7 +21,6 @@ const PART_MIN_COUNT: usize = 8;
 const PART_DATE: usize = 5;
 const PART_TIME: usize = 6;
 
-const EXEC_ERROR: &str = "Exec format error";
 
 
 pub enum Params {
@@ -53,12 +53,12 @@ impl Display for Params {
 
 pub fn pull_screenshots(params: Params, config: &Config) -> ExitCode {
     let command = get_ls_command(&config.screenshots.sources);
-    return pull(params, PICS, &[SHELL, command.as_str()], config.screenshot_hook(), &config.screenshots.destination)
+    return pull(params, PICS, &[SHELL, command.as_str()], config.screenshot_hooks(), &config.screenshots.destination, config)
 }
 
 pub fn pull_screencasts(params: Params, config: &Config) -> ExitCode {
     let command = get_ls_command(&config.screencasts.sources);
-    return pull(params, MOVS, &[SHELL, command.as_str()], config.screencast_hook(), &config.screencasts.destination)
+    return pull(params, MOVS, &[SHELL, command.as_str()], config.screencast_hooks(), &config.screencasts.destination, config)
 }
 
 fn get_ls_command(sources: &Vec<String>) -> String {
@@ -71,7 +71,7 @@ fn get_ls_command(sources: &Vec<String>) -> String {
     return command;
 }
 
-fn pull(params: Params, exts: &[&str], args: &[&str], hook: Option<PathBuf>, default_dst: &String) -> ExitCode {
+fn pull(params: Params, exts: &[&str], args: &[&str], hooks: HookSet, default_dst: &String, config: &Config) -> ExitCode {
     let count = match params {
         Params::Count(_, count) => count,
         Params::Single(..) => 1,
@@ -104,9 +104,10 @@ fn pull(params: Params, exts: &[&str], args: &[&str], hook: Option<PathBuf>, def
             .take(count)
             .map(|it| it.path.to_string())
             .collect::<Vec<String>>();
+        let template = Template::new(&device, config);
         let (cmd, dst) = match params {
             Params::Count(cmd, _) => {
-                let dst = default_dst.dst();
+                let dst = template.dir(default_dst);
                 fs::create_dir_all(&dst).unwrap();
                 (cmd,dst)
             },
@@ -114,52 +115,33 @@ fn pull(params: Params, exts: &[&str], args: &[&str], hook: Option<PathBuf>, def
                 let name = Path::new(items.first().unwrap())
                     .file_name().unwrap()
                     .to_str().unwrap();
-                let dst = path.unwrap_or_default()
-                    .dst_with_parent(&default_dst)
+                let dst = template.path(&path.unwrap_or_default(), default_dst)
                     .join(name);
+                let dst = numbered(dst);
                 ensure_parent_exists(&dst);
                 (cmd,dst)
             },
         };
+        let context = HookContext::new(&cmd, &device, &dst);
+        let code = hooks.run(&context);
+        if code != ExitCode::SUCCESS {
+            return code
+        }
         let mut pull_args = AdbArgs::spawn(&[PULL]);
         items.reverse();
-        let hook = hook_or_none(hook, cmd, dst.clone(), &items);
+        let files = match dst.is_dir() {
+            true => items.iter().map(|it| dst.join(it.file_name())).collect::<Vec<PathBuf>>(),
+            false => vec![dst.clone()],
+        };
         pull_args.args.append(&mut items);
         pull_args.args.push(dst.to_string());
         let output = run_adb_with(&device, pull_args);
         output.print_out_and_err();
-        if output.status.success() {
-            SAVED.println_formatted(&[&dst.to_string()]);
-        }
-        hook.ok_or(ExitCode::SUCCESS)
-            .and_then(|mut cmd| {
-                check_exec_error(cmd.spawn()).and_then(|child| child.wait_with_output()).map_err(|e| { 
-                    e.eprintln();
-                    ExitCode::FAILURE
-                })
-            })
-            .unwrap_or(output)
-            .exit_code()
-    }
-}
-
-fn hook_or_none(hook: Option<PathBuf>, cmd: String, dst: PathBuf, items: &Vec<String>) -> Option<Command> {
-    match hook {
-        Some(hook) => {
-            let mut command = Command::new(hook);
-            command.arg(cmd);
-            match fs::metadata(&dst).map(|it| it.is_file()) {
-                Err(_) => return None,
-                Ok(true) => { command.arg(dst); },
-                Ok(false) => {
-                    for it in items {
-                        command.arg(dst.clone().join(it.file_name().as_str()));
-                    }
-                },
-            }
-            Some(command)
+        if !output.status.success() {
+            return output.exit_code()
         }
-        None => None,
+        SAVED.println_formatted(&[&dst.to_string()]);
+        hooks.run(&context.post(&files))
     }
 }
 
@@ -206,15 +188,6 @@ fn splitn_by(str: &str, limit: usize, sep: char) -> Vec<String> {
     return parts;
 }
 
-fn check_exec_error(child: io::Result<Child>) -> io::Result<Child> {
-    if let Err(err) = &child {
-        if err.to_string().starts_with(EXEC_ERROR) {
-            ADD_INTERPRETER.value().to_string();
-        }
-    }
-    return child
-}
-
 struct Item {
     pub date: String,
     pub time: String,
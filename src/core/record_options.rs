@@ -0,0 +1,121 @@
+use crate::core::config::RecordOptions;
+use crate::core::flags::Flags;
+use crate::core::strings::{INVALID_OPTION_VALUE, OPTION_REQUIRES_SDK};
+use regex::Regex;
+
+pub const BIT_RATE: &str = "bit-rate";
+pub const SIZE: &str = "size";
+pub const TIME_LIMIT: &str = "time-limit";
+pub const ROTATE: &str = "rotate";
+pub const BUGREPORT: &str = "bugreport";
+pub const CODEC: &str = "codec";
+
+pub const SWITCHES: [&str; 2] = [ROTATE, BUGREPORT];
+pub const OPTIONS: [&str; 4] = [BIT_RATE, SIZE, TIME_LIMIT, CODEC];
+
+const CODEC_NAME: &str = "codec-name";
+const MAX_TIME_LIMIT: u32 = 180;
+const SDK_BUGREPORT: usize = 23;
+const SDK_CODEC_NAME: usize = 29;
+const SDK_UNLIMITED_TIME: usize = 34;
+
+impl RecordOptions {
+    pub fn parse(args: &[String]) -> Result<RecordOptions, String> {
+        let flags = Flags::parse(args, &SWITCHES, &OPTIONS)?;
+        return RecordOptions::from_flags(&flags)
+    }
+
+    pub fn from_flags(flags: &Flags) -> Result<RecordOptions, String> {
+        let time_limit = match flags.value(TIME_LIMIT) {
+            None => None,
+            Some(value) => Some(value.parse::<u32>().map_err(|_| invalid(TIME_LIMIT, &value))?),
+        };
+        return Ok(RecordOptions {
+            bit_rate: flags.value(BIT_RATE),
+            size: flags.value(SIZE),
+            time_limit,
+            rotate: flags.has(ROTATE).then_some(true),
+            bugreport: flags.has(BUGREPORT).then_some(true),
+            codec: flags.value(CODEC),
+        })
+    }
+
+    /// The values of `other` take precedence.
+    pub fn merge(&self, other: &RecordOptions) -> RecordOptions {
+        RecordOptions {
+            bit_rate: other.bit_rate.clone().or(self.bit_rate.clone()),
+            size: other.size.clone().or(self.size.clone()),
+            time_limit: other.time_limit.or(self.time_limit),
+            rotate: other.rotate.or(self.rotate),
+            bugreport: other.bugreport.or(self.bugreport),
+            codec: other.codec.clone().or(self.codec.clone()),
+        }
+    }
+
+    /// Validates the options against the device SDK, if it's known, and makes the screenrecord arguments.
+    pub fn to_args(&self, sdk: Option<usize>) -> Result<Vec<String>, String> {
+        let mut args = vec![];
+        if let Some(bit_rate) = &self.bit_rate {
+            args.push(format!("--{BIT_RATE}"));
+            args.push(parse_bit_rate(bit_rate).ok_or_else(|| invalid(BIT_RATE, bit_rate))?.to_string());
+        }
+        if let Some(size) = &self.size {
+            if !Regex::new(r"^\d+x\d+$").unwrap().is_match(size) {
+                return Err(invalid(SIZE, size))
+            }
+            args.push(format!("--{SIZE}"));
+            args.push(size.clone());
+        }
+        if let Some(limit) = self.time_limit {
+            let unlimited = sdk.map(|it| it >= SDK_UNLIMITED_TIME).unwrap_or(false);
+            match () {
+                _ if limit == 0 && unlimited => (),
+                _ if limit == 0 => return Err(invalid(TIME_LIMIT, &limit.to_string())),
+                _ if limit > MAX_TIME_LIMIT && !unlimited => return Err(invalid(TIME_LIMIT, &limit.to_string())),
+                _ => (),
+            }
+            args.push(format!("--{TIME_LIMIT}"));
+            args.push(limit.to_string());
+        }
+        if self.rotate == Some(true) {
+            args.push(format!("--{ROTATE}"));
+        }
+        if self.bugreport == Some(true) {
+            require_sdk(BUGREPORT, SDK_BUGREPORT, sdk)?;
+            args.push(format!("--{BUGREPORT}"));
+        }
+        if let Some(codec) = &self.codec {
+            require_sdk(CODEC, SDK_CODEC_NAME, sdk)?;
+            if !Regex::new(r"^[\w.\-]+$").unwrap().is_match(codec) {
+                return Err(invalid(CODEC, codec))
+            }
+            args.push(format!("--{CODEC_NAME}"));
+            args.push(codec.clone());
+        }
+        return Ok(args)
+    }
+}
+
+fn parse_bit_rate(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.chars().last()? {
+        'k' | 'K' => (&value[..value.len() - 1], 1_000.0),
+        'm' | 'M' => (&value[..value.len() - 1], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+    let number = number.parse::<f64>().ok()?;
+    return match number * multiplier {
+        rate if rate >= 1.0 => Some(rate as u64),
+        _ => None,
+    }
+}
+
+fn require_sdk(option: &str, min: usize, sdk: Option<usize>) -> Result<(), String> {
+    match sdk {
+        Some(sdk) if sdk < min => Err(OPTION_REQUIRES_SDK.formatted(&[&format!("--{option}"), &min.to_string(), &sdk.to_string()])),
+        _ => Ok(()),
+    }
+}
+
+fn invalid(option: &str, value: &str) -> String {
+    INVALID_OPTION_VALUE.formatted(&[&format!("--{option}"), value])
+}
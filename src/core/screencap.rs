@@ -1,46 +1,192 @@
 use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
 use crate::core::config::Config;
-use crate::core::destination::Destination;
-use crate::core::ext::{OutputExt, PathBufExt, VecExt};
+use crate::core::demo::DemoMode;
+use crate::core::ext::{OutputExt, PathBufExt, PrintExt, VecExt};
+use crate::core::flags::Flags;
+use crate::core::hook::HookContext;
 use crate::core::r#const::SHELL;
 use crate::core::selector::{resolve_device, run_adb_with};
-use crate::core::strings::SAVED;
-use crate::core::util::{ensure_parent_exists, format_file_name, try_run_hook_and_exit};
+use crate::core::strings::{CAPTURES_FAILED, INVALID_OPTION_VALUE, PRESS_ENTER_TO_STOP_CAPTURE, SAVED};
+use crate::core::system::enter_pressed;
+use crate::core::template::Template;
+use crate::core::util::{ensure_parent_exists, parse_duration};
+use std::collections::VecDeque;
 use std::fs;
-use std::process::ExitCode;
+use std::path::PathBuf;
+use std::process::{ExitCode, Output};
+use std::thread;
+use std::thread::ScopedJoinHandle;
+use std::time::{Duration, Instant};
 
 const SCREENCAP_P: &str = "screencap -p";
 const OD: u8 = 0x0D;
 const OA: u8 = 0x0A;
+const BURST: &str = "burst";
+const EVERY: &str = "every";
+const FOR: &str = "for";
+const MAX_IN_FLIGHT: usize = 4;
 
-pub fn make_screenshot(cmd: String, dst: String, config: &Config) -> ExitCode {
+pub fn make_screenshot(cmd: String, args: &[String], config: &Config) -> ExitCode {
+    let flags = match Flags::parse(args, &[], &[BURST, EVERY, FOR]) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let series = match Series::from(&flags) {
+        Ok(series) => series,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let dst = flags.arg(0).unwrap_or_default();
     let device = match resolve_device() {
         Ok(device) => device,
         Err(code) => return code,
     };
-    let args = &[SHELL, SCREENCAP_P];
-    let output = run_adb_with(&device, AdbArgs::run(args));
-
-    if output.status.success() {
-        let code = output.exit_code();
-        let dst = dst
-            .dst_with_parent(&config.screenshots.destination)
-            .join(format_file_name(&config.screenshots.name));
-        ensure_parent_exists(&dst);
-
-        let bytes = match &output.stdout[4..=5] {
-            &[OD, OA] => output.stdout,
-            _ => filter_extra_zero_d(output.stdout),
+    let template = Template::new(&device, config);
+    let dst = match series {
+        None => template.file(&dst, &config.screenshots.destination, &config.screenshots.name),
+        Some(_) => template.file(&dst, &config.screenshots.destination, &config.screenshots.series_name),
+    };
+    let hooks = config.screenshot_hooks();
+    let context = HookContext::new(&cmd, &device, &dst);
+    let code = hooks.run(&context);
+    if code != ExitCode::SUCCESS {
+        return code
+    }
+    let demo = match config.screenshots.demo_mode {
+        false => None,
+        true => match DemoMode::enter(&device, &config.demo) {
+            Ok(demo) => demo,
+            Err(e) => {
+                e.eprintln();
+                return ExitCode::FAILURE
+            }
+        },
+    };
+    let files = match series {
+        Some(series) => capture_series(&device, &series, &dst),
+        None => capture_to(&device, &dst).map(|it| (vec![it], 0)),
+    };
+    if let Some(demo) = demo {
+        demo.exit(&device);
+    }
+    let (files, failed) = match files {
+        Ok(files) => files,
+        Err(output) => {
+            output.print_err();
+            return output.exit_code()
+        }
+    };
+    SAVED.println_formatted(&[&dst.to_string()]);
+    let code = hooks.run(&context.post(&files));
+    return match failed {
+        0 => code,
+        _ => ExitCode::FAILURE,
+    }
+}
+
+/// A sequence of screenshots, it lasts until the count is reached, the time is up or Enter is pressed.
+struct Series {
+    count: Option<usize>,
+    every: Duration,
+    duration: Option<Duration>,
+}
+
+impl Series {
+    fn from(flags: &Flags) -> Result<Option<Series>, String> {
+        let count = match flags.value(BURST) {
+            None => None,
+            Some(value) => match value.parse::<usize>() {
+                Ok(count) if count > 0 => Some(count),
+                _ => return Err(INVALID_OPTION_VALUE.formatted(&[&format!("--{BURST}"), &value])),
+            },
         };
-        fs::write(&dst, bytes).unwrap();
-        SAVED.println_formatted(&[&dst.to_string()]);
-        config.screenshot_hook()
-            .map(|hook| try_run_hook_and_exit(hook, cmd, dst))
-            .unwrap_or(code)
-    } else {
-        output.print_err();
-        return output.exit_code()
+        let duration = |name: &str| match flags.value(name) {
+            None => Ok(None),
+            Some(value) => parse_duration(&value)
+                .map(Some)
+                .ok_or_else(|| INVALID_OPTION_VALUE.formatted(&[&format!("--{name}"), &value])),
+        };
+        let every = duration(EVERY)?;
+        let duration = duration(FOR)?;
+        if count.is_none() && every.is_none() && duration.is_none() {
+            return Ok(None)
+        }
+        let every = every.unwrap_or(Duration::ZERO);
+        return Ok(Some(Series { count, every, duration }))
+    }
+}
+
+/// The captures are pipelined, so the interval isn't stretched by the adb latency.
+/// Returns the files and the number of the failed captures, it fails if none succeeded.
+fn capture_series(device: &AdbDevice, series: &Series, dir: &PathBuf) -> Result<(Vec<PathBuf>, usize), Output> {
+    fs::create_dir_all(dir).unwrap();
+    if series.count.is_none() || series.every > Duration::ZERO {
+        PRESS_ENTER_TO_STOP_CAPTURE.println();
+    }
+    let start = Instant::now();
+    let mut files = vec![];
+    let mut failures = vec![];
+    thread::scope(|scope| {
+        let mut in_flight: VecDeque<ScopedJoinHandle<Result<PathBuf, Output>>> = VecDeque::new();
+        let mut collect = |handle: ScopedJoinHandle<Result<PathBuf, Output>>| match handle.join().unwrap() {
+            Ok(file) => files.push(file),
+            Err(output) => failures.push(output),
+        };
+        for index in 0.. {
+            let due = start + series.every * index as u32;
+            let finished = series.count.map(|it| index >= it).unwrap_or(false)
+                || series.duration.map(|it| due > start + it).unwrap_or(false);
+            if finished || enter_pressed(due.saturating_duration_since(Instant::now())) {
+                break
+            }
+            while in_flight.len() >= MAX_IN_FLIGHT {
+                collect(in_flight.pop_front().unwrap());
+            }
+            let file = dir.join(format!("{:04}.png", index + 1));
+            in_flight.push_back(scope.spawn(move || capture_to(device, &file)));
+        }
+        in_flight.into_iter().for_each(collect);
+    });
+    if files.is_empty() {
+        if let Some(output) = failures.pop() {
+            return Err(output)
+        }
     }
+    if !failures.is_empty() {
+        failures.iter().for_each(|it| it.print_err());
+        CAPTURES_FAILED.formatted(&[&failures.len().to_string(), &(failures.len() + files.len()).to_string()]).eprintln();
+    }
+    files.sort();
+    return Ok((files, failures.len()))
+}
+
+fn capture_to(device: &AdbDevice, dst: &PathBuf) -> Result<PathBuf, Output> {
+    let bytes = capture(device)?;
+    ensure_parent_exists(dst);
+    fs::write(dst, bytes).unwrap();
+    return Ok(dst.clone())
+}
+
+/// Returns the PNG bytes of the current screen.
+pub fn capture(device: &AdbDevice) -> Result<Vec<u8>, Output> {
+    let args = &[SHELL, SCREENCAP_P];
+    let output = run_adb_with(device, AdbArgs::run(args));
+    if !output.status.success() {
+        return Err(output)
+    }
+    // shorter than the PNG signature, nothing was captured
+    let bytes = match output.stdout.get(4..=5) {
+        None => return Err(output),
+        Some(&[OD, OA]) => output.stdout,
+        Some(_) => filter_extra_zero_d(output.stdout),
+    };
+    return Ok(bytes)
 }
 
 fn filter_extra_zero_d(src: Vec<u8>) -> Vec<u8> {
@@ -1,65 +1,293 @@
 use crate::core::adb_command::AdbArgs;
-use crate::core::config::Config;
-use crate::core::destination::Destination;
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::{Config, ConvertFormat, Profile, RecordOptions, Toggle};
+use crate::core::convert::convert_screencast;
+use crate::core::demo::DemoMode;
 use crate::core::ext::{OutputExt, PathBufExt, PrintExt};
-use crate::core::r#const::{PULL, SHELL};
+use crate::core::flags::Flags;
+use crate::core::logcat::LogcatRecorder;
+use crate::core::mp4::{AnnexB, Mp4Writer};
+use crate::core::profile::{apply, current, no_profile};
+use crate::core::r#const::{EXEC_OUT, OFF, ON, PULL, SHELL, TAPS};
+use crate::core::record_options::{OPTIONS, SWITCHES};
 use crate::core::selector::{adb_args_with, resolve_device, run_adb_with};
 use crate::core::strings::{PRESS_ENTER_TO_STOP_REC, SAVED};
 use crate::core::system::interrupt;
-use crate::core::taps::{is_taps_on, turn_taps};
-use crate::core::util::{ensure_parent_exists, format_file_name, try_run_hook_and_exit};
+use crate::core::hook::HookContext;
+use crate::core::template::Template;
+use crate::core::util::ensure_parent_exists;
 use std::io;
-use std::process::ExitCode;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, ExitCode, Stdio};
+use std::thread;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 const SCREENRECORD: &str = "screenrecord";
+const OUTPUT_FORMAT_H264: &str = "--output-format=h264";
+const TO_STDOUT: &str = "-";
 const TMP: &str = "/data/local/tmp/record.mp4";
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+const GIF: &str = "gif";
+const WEBM: &str = "webm";
+const LOGCAT: &str = "logcat";
+const PROFILE: &str = "profile";
+const LOG_EXTENSION: &str = "log";
 
-pub fn make_screencast(cmd: String, dst: String, config: &Config) -> ExitCode {
+pub fn make_screencast(cmd: String, args: &[String], config: &Config) -> ExitCode {
+    let switches = [&[GIF, WEBM, LOGCAT], SWITCHES.as_slice()].concat();
+    // --logcat or --logcat=package, a switch goes first so the arg after a bare --logcat stays positional
+    let options = [&[LOGCAT, PROFILE], OPTIONS.as_slice()].concat();
+    let flags = match Flags::parse(args, &switches, &options) {
+        Ok(flags) => flags,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let mut positional = flags.positional.iter();
+    let preset = flags.arg(0).and_then(|it| config.screencasts.presets.get(&it));
+    if preset.is_some() {
+        positional.next();
+    }
+    let options = match RecordOptions::from_flags(&flags) {
+        Ok(options) => config.screencasts.options
+            .merge(preset.unwrap_or(&RecordOptions::default()))
+            .merge(&options),
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let convert = match () {
+        _ if flags.has(GIF) => Some(ConvertFormat::Gif),
+        _ if flags.has(WEBM) => Some(ConvertFormat::Webm),
+        _ => config.screencasts.convert.format,
+    };
+    let profile = match flags.value(PROFILE).or(config.screencasts.profile.clone()) {
+        None => None,
+        Some(name) => match config.profiles.get(&name) {
+            Some(profile) => Some(profile),
+            None => {
+                no_profile(&name, config);
+                return ExitCode::FAILURE
+            }
+        },
+    };
+    let dst = positional.next().cloned().unwrap_or_default();
     let device = match resolve_device() {
         Ok(device) => device,
         Err(code) => return code,
     };
+    let options = match options.to_args(device.sdk) {
+        Ok(options) => options,
+        Err(e) => {
+            e.eprintln();
+            return ExitCode::FAILURE
+        }
+    };
+    let dst = Template::new(&device, config)
+        .file(&dst, &config.screencasts.destination, &config.screencasts.name);
+    ensure_parent_exists(&dst);
+    let hooks = config.screencast_hooks();
+    let context = HookContext::new(&cmd, &device, &dst);
+    let code = hooks.run(&context);
+    if code != ExitCode::SUCCESS {
+        return code
+    }
+    let start = Instant::now();
+    let log = dst.with_extension(LOG_EXTENSION);
+    let mut recording = Recording { device: &device, logcat: None, previous: None, demo: None, taps: None };
+    if flags.has(LOGCAT) {
+        match LogcatRecorder::start(&device, flags.value(LOGCAT).as_ref(), &log, start) {
+            Ok(logcat) => recording.logcat = Some(logcat),
+            Err(e) => {
+                e.eprintln();
+                return ExitCode::FAILURE
+            }
+        }
+    }
+    if let Some(profile) = profile {
+        match apply_profile(&device, profile) {
+            Ok(previous) => recording.previous = Some(previous),
+            Err(e) => {
+                e.eprintln();
+                return ExitCode::FAILURE
+            }
+        }
+    }
+    if config.screenshots.demo_mode {
+        match DemoMode::enter(&device, &config.demo) {
+            Ok(demo) => recording.demo = demo,
+            Err(e) => {
+                e.eprintln();
+                return ExitCode::FAILURE
+            }
+        }
+    }
     let show_taps = config.screencasts.show_taps;
-    let toggle_taps = show_taps != is_taps_on(&device);
-    if toggle_taps {
-        turn_taps(&device, show_taps);
+    let taps = &config.toggles[TAPS];
+    let (shown, hidden) = match show_taps {
+        true => (ON, OFF),
+        false => (OFF, ON),
+    };
+    if show_taps != taps.is_on(&device) {
+        taps.set(&device, shown);
+        recording.taps = Some((taps, hidden));
+    }
+    let result = match config.screencasts.stream {
+        true => record_stream(&device, &options, &dst),
+        false => record_on_device(&device, &options, &dst),
+    };
+    let logcat = recording.finish();
+    if let Err(code) = result {
+        return code
+    }
+    SAVED.println_formatted(&[&dst.to_string()]);
+    let converted = convert.and_then(|format| {
+        convert_screencast(&dst, format, &config.screencasts.convert)
+            .map_err(|e| e.eprintln())
+            .ok()
+    });
+    let log = match logcat {
+        Some(Ok(_)) => Some(log),
+        Some(Err(e)) => {
+            e.eprintln();
+            None
+        },
+        None => None,
+    };
+    for file in converted.iter().chain(log.iter()) {
+        SAVED.println_formatted(&[&file.to_string()]);
+    }
+    let mut files = vec![dst];
+    files.retain(|it| it.exists());
+    files.extend(converted);
+    files.extend(log);
+    return hooks.run(&context.post(&files))
+}
+
+/// What a recording changes on the device, brought back on every exit path.
+struct Recording<'a> {
+    device: &'a AdbDevice,
+    logcat: Option<LogcatRecorder>,
+    /// only the values of the profile
+    previous: Option<Profile>,
+    demo: Option<DemoMode>,
+    taps: Option<(&'a Toggle, &'static str)>,
+}
+
+impl Recording<'_> {
+    /// Restores the device and stops the logcat recorder.
+    fn finish(mut self) -> Option<io::Result<()>> {
+        self.restore();
+        return self.logcat.take().map(|it| it.stop())
+    }
+
+    /// In the reverse order of the changes.
+    fn restore(&mut self) {
+        if let Some((taps, value)) = self.taps.take() {
+            taps.set(self.device, value);
+        }
+        if let Some(demo) = self.demo.take() {
+            demo.exit(self.device);
+        }
+        if let Some(Err(e)) = self.previous.take().map(|it| apply(self.device, &it)) {
+            e.eprintln();
+        }
+    }
+}
+
+impl Drop for Recording<'_> {
+    fn drop(&mut self) {
+        self.restore();
+        if let Some(logcat) = self.logcat.take() {
+            let _ = logcat.stop();
+        }
     }
-    let args = &[SHELL, SCREENRECORD, &config.screencasts.args, TMP];
-    let args = adb_args_with(&device, AdbArgs::spawn(args));
+}
+
+fn apply_profile(device: &AdbDevice, profile: &Profile) -> Result<Profile, String> {
+    let mut previous = current(device)?;
+    previous.retain(|key, _| profile.contains_key(key));
+    apply(device, profile)?;
+    return Ok(previous)
+}
+
+fn record_stream(device: &AdbDevice, options: &[String], dst: &Path) -> Result<(), ExitCode> {
+    let args = [&[EXEC_OUT, SCREENRECORD, OUTPUT_FORMAT_H264], options_as_str(options).as_slice(), &[TO_STDOUT]].concat();
+    let mut command = screenrecord_command(device, &args)?;
+    command.stdout(Stdio::piped());
+    let mut writer = Mp4Writer::create(dst).map_err(|e| {
+        e.eprintln();
+        ExitCode::FAILURE
+    })?;
+    let mut child = command.spawn().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    let muxer = thread::spawn(move || -> io::Result<()> {
+        let mut splitter = AnnexB::new();
+        let mut buf = vec![0u8; READ_BUFFER_SIZE];
+        loop {
+            let count = stdout.read(&mut buf)?;
+            if count == 0 {
+                break
+            }
+            let now = Instant::now();
+            for nal in splitter.push(&buf[..count]) {
+                writer.push_nal(&nal, now)?;
+            }
+        }
+        return writer.finish()
+    });
+    wait_for_stop();
+    interrupt(child.id());
+    child.wait().unwrap();
+    return muxer.join()
+        .unwrap()
+        .map_err(|e| {
+            e.eprintln();
+            ExitCode::FAILURE
+        })
+}
+
+fn record_on_device(device: &AdbDevice, options: &[String], dst: &Path) -> Result<(), ExitCode> {
+    let args = [&[SHELL, SCREENRECORD], options_as_str(options).as_slice(), &[TMP]].concat();
+    let mut child = screenrecord_command(device, &args)?.spawn().unwrap();
+    wait_for_stop();
+    interrupt(child.id());
+    child.wait().unwrap();
+    sleep(Duration::from_secs(1));
+    let output = run_adb_with(device, AdbArgs::run(&[PULL, TMP, &dst.to_string_lossy()]));
+    output.print_out_and_err();
+    return match output.status.success() {
+        true => Ok(()),
+        false => Err(output.exit_code()),
+    }
+}
+
+fn screenrecord_command(device: &AdbDevice, args: &[&str]) -> Result<Command, ExitCode> {
+    let args = adb_args_with(device, AdbArgs::spawn(args));
+    #[allow(unused_mut)] // mutated on Windows only
     let mut command = match args.command() {
         Ok(c) => c,
         Err(e) => {
             e.eprintln();
-            return ExitCode::FAILURE;
+            return Err(ExitCode::FAILURE);
         }
     };
     #[cfg(windows)] {
         use std::os::windows::process::CommandExt;
         command.creation_flags(0x0000_0200u32); // CREATE_NEW_PROCESS_GROUP
     }
-    let mut child = command.spawn().unwrap();
+    return Ok(command)
+}
+
+fn options_as_str(options: &[String]) -> Vec<&str> {
+    options.iter().map(String::as_str).collect()
+}
+
+fn wait_for_stop() {
     PRESS_ENTER_TO_STOP_REC.print();
     io::stdin().read_line(&mut String::new()).unwrap();
-    interrupt(child.id());
-    child.wait().unwrap();
-    if toggle_taps {
-        turn_taps(&device, !show_taps);
-    }
-    sleep(Duration::from_secs(1));
-    let dst = dst
-        .dst_with_parent(&config.screencasts.destination)
-        .join(format_file_name(&config.screencasts.name));
-    ensure_parent_exists(&dst);
-    let output = run_adb_with(&device, AdbArgs::run(&[PULL, TMP, dst.to_str()]));
-    output.print_out_and_err();
-    let mut code = output.exit_code();
-    if output.status.success() {
-        SAVED.println_formatted(&[&dst.to_string()]);
-        code = config.screencast_hook()
-            .map(|hook| try_run_hook_and_exit(hook, cmd, dst))
-            .unwrap_or(code)
-    }
-    return code
 }
@@ -3,7 +3,7 @@ use crate::core::adb_device::{AdbDevice, AdbDeviceVec};
 use crate::core::ext::{print_no_one, OutputExt, PrintExt, StringExt, VecExt};
 use crate::core::fix::sudo_fix_on_linux;
 use crate::core::r#const::SHELL;
-use crate::core::strings::{ERROR, SELECT_DEVICE, UNAUTHORIZED_BY_DEVICE, UNKNOWN};
+use crate::core::strings::{DEVICE_NOT_FOUND, ERROR, SELECT_DEVICE, UNAUTHORIZED_BY_DEVICE, UNKNOWN};
 use crate::core::system::error_exit_status;
 use crate::core::util::{failure, interactive_select, string};
 use itertools::Itertools;
@@ -123,8 +123,11 @@ pub fn fetch_adb_devices() -> Vec<AdbDevice> {
             let ok = parts[1] == DEVICE;
             let unauthorized = parts[1] == UNAUTHORIZED;
             let no_permissions = parts[1].starts_with(NO_PERMISSIONS);
-            let model = if ok { get_description(&serial) } else { serial.clone() };
-            AdbDevice { serial, model, ok, unauthorized, no_permissions }
+            let (description, model, sdk) = match ok {
+                true => get_description(&serial),
+                false => (serial.clone(), serial.clone(), None),
+            };
+            AdbDevice { serial, description, model, sdk, ok, unauthorized, no_permissions }
         }).collect::<Vec<AdbDevice>>();
 }
 
@@ -145,6 +148,30 @@ pub fn resolve_device() -> Result<AdbDevice, ExitCode> {
     return Ok(device);
 }
 
+/// The device with the serial or the model, or the picked one if there is no query.
+/// The excluded one is never picked, for the commands working with two devices.
+pub fn device_by(query: Option<String>, excluded: Option<&AdbDevice>) -> Result<AdbDevice, ExitCode> {
+    let mut devices = fetch_adb_devices();
+    devices.retain(|it| excluded.map(|excluded| excluded.serial != it.serial).unwrap_or(true));
+    let query = match query {
+        Some(query) => query,
+        None if devices.is_empty() => {
+            print_no_one();
+            return failure()
+        },
+        None => return ask_for_device(devices),
+    };
+    let position = devices.iter().position(|it| it.serial == query)
+        .or_else(|| devices.iter().position(|it| it.model.eq_ignore_ascii_case(&query)));
+    return match position {
+        Some(position) if devices[position].ok => Ok(devices.remove(position)),
+        _ => {
+            DEVICE_NOT_FOUND.formatted(&[&query]).eprintln();
+            failure()
+        },
+    }
+}
+
 fn ask_for_device(devices: Vec<AdbDevice>) -> Result<AdbDevice, ExitCode> {
     interactive_select(SELECT_DEVICE.value(), devices, |device, devices| {
         let status = match () {
@@ -156,10 +183,11 @@ fn ask_for_device(devices: Vec<AdbDevice>) -> Result<AdbDevice, ExitCode> {
     })
 }
 
-fn get_description(serial: &String) -> String {
+/// Returns the description, the model name and the SDK version.
+fn get_description(serial: &String) -> (String, String, Option<usize>) {
     let output = run_adb(AdbArgs::run(&[ARG_S, serial.as_str(), SHELL, GETPROPS]));
     if !output.status.success() {
-        return serial.clone();
+        return (serial.clone(), serial.clone(), None);
     }
     let stdout = output.stdout();
     let mut properties = stdout.split('\n')
@@ -167,10 +195,11 @@ fn get_description(serial: &String) -> String {
         .collect::<Vec<String>>();
     let sdk = properties.remove(0).parse::<usize>();
     let version = VERSIONS.get(sdk.clone().unwrap_or(VERSIONS.len())).unwrap_or(&"n/a");
-    let version = format!("{version} [{}]", sdk.unwrap_or(0));
+    let version = format!("{version} [{}]", sdk.clone().unwrap_or(0));
+    let sdk = sdk.ok();
 
     let index = match properties.index_of(|it| it == "anime") {
-        None => return serial.clone(),
+        None => return (serial.clone(), serial.clone(), sdk),
         Some(index) => index,
     };
     let mut vendor = properties[0..index].iter()
@@ -215,7 +244,8 @@ fn get_description(serial: &String) -> String {
         Some(vendor) => format!("{vendor}: "),
         None => string(""),
     };
-    return format!("{prefix}{}, serial: {serial}, Android {version}", suitable.join(", "))
+    let model = suitable.first().cloned().unwrap_or(serial.clone());
+    return (format!("{prefix}{}, serial: {serial}, Android {version}", suitable.join(", ")), model, sdk)
 }
 
 fn run_adb(args: AdbArgs) -> Output {
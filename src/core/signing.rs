@@ -0,0 +1,160 @@
+use crate::core::ext::ResultExt;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use zip::ZipArchive;
+
+const EOCD_MAGIC: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+const EOCD_SIZE: u64 = 22;
+const MAX_COMMENT: u64 = 0xFFFF;
+const SIGNING_BLOCK_MAGIC: &[u8; 16] = b"APK Sig Block 42";
+// the block ends with its size and the magic
+const SIGNING_BLOCK_FOOTER: u64 = 24;
+// preferred first
+const SCHEMES: &[(u32, &str)] = &[(0x1b93_ad61, "v3.1"), (0xf053_68c0, "v3"), (0x7109_871a, "v2")];
+const META_INF: &str = "META-INF/";
+const V1_SIGNATURES: &[&str] = &[".RSA", ".DSA", ".EC"];
+const V1: &str = "v1";
+const DER_SEQUENCE: u8 = 0x30;
+const DER_OID: u8 = 0x06;
+const DER_CONTEXT_0: u8 = 0xA0;
+
+/// The first signing certificate of an APK.
+pub struct Signature {
+    pub scheme: &'static str,
+    /// upper case hex with colons, as apksigner and keytool print it
+    pub sha256: String,
+}
+
+/// Looks into the APK Signing Block, then into META-INF for the v1 signature.
+pub fn signature(apk: &Path) -> Result<Option<Signature>, String> {
+    let mut file = File::open(apk).string_err()?;
+    let certificate = match signing_block_certificate(&mut file) {
+        Some(found) => Some(found),
+        None => v1_certificate(file)?.map(|it| (V1, it)),
+    };
+    return Ok(certificate.map(|(scheme, certificate)| Signature { scheme, sha256: sha256(&certificate) }))
+}
+
+pub fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter()
+        .map(|it| format!("{it:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn signing_block_certificate(file: &mut File) -> Option<(&'static str, Vec<u8>)> {
+    let length = file.metadata().ok()?.len();
+    let tail_length = length.min(EOCD_SIZE + MAX_COMMENT);
+    let tail = read_at(file, length - tail_length, tail_length)?;
+    let eocd = (0..=tail.len().checked_sub(EOCD_SIZE as usize)?)
+        .rev()
+        .find(|it| tail[*it..*it + 4] == EOCD_MAGIC)?;
+    let central_directory = u32_at(&tail, eocd + 16)? as u64;
+    let footer = read_at(file, central_directory.checked_sub(SIGNING_BLOCK_FOOTER)?, SIGNING_BLOCK_FOOTER)?;
+    if &footer[8..] != SIGNING_BLOCK_MAGIC {
+        return None
+    }
+    // the size doesn't count the leading size field
+    let size = u64::from_le_bytes(footer[..8].try_into().ok()?);
+    let start = central_directory.checked_sub(size)?;
+    let pairs = read_at(file, start, size.checked_sub(SIGNING_BLOCK_FOOTER)?)?;
+    let mut offset = 0;
+    let mut values = vec![];
+    while offset + 12 <= pairs.len() {
+        let length = u64::from_le_bytes(pairs[offset..offset + 8].try_into().ok()?) as usize;
+        let id = u32_at(&pairs, offset + 8)?;
+        let value = pairs.get(offset + 12..(offset + 8).checked_add(length)?)?;
+        values.push((id, value));
+        offset += 8 + length;
+    }
+    return SCHEMES.iter().find_map(|(id, scheme)| {
+        let value = values.iter().find(|(it, _)| it == id)?.1;
+        return Some((*scheme, scheme_certificate(value)?))
+    })
+}
+
+/// signers → the first signer → signed data → (digests, certificates) → the first certificate,
+/// everything is prefixed with its u32 length.
+fn scheme_certificate(value: &[u8]) -> Option<Vec<u8>> {
+    let (signers, _) = prefixed(value, 0)?;
+    let (signer, _) = prefixed(signers, 0)?;
+    let (signed_data, _) = prefixed(signer, 0)?;
+    let (_, offset) = prefixed(signed_data, 0)?;
+    let (certificates, _) = prefixed(signed_data, offset)?;
+    let (certificate, _) = prefixed(certificates, 0)?;
+    return Some(certificate.to_vec())
+}
+
+fn v1_certificate(file: File) -> Result<Option<Vec<u8>>, String> {
+    let mut zip = ZipArchive::new(file).string_err()?;
+    let name = zip.file_names()
+        .find(|it| it.starts_with(META_INF) && V1_SIGNATURES.iter().any(|ext| it.to_uppercase().ends_with(ext)))
+        .map(String::from);
+    let name = match name {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let mut bytes = vec![];
+    zip.by_name(&name).string_err()?.read_to_end(&mut bytes).string_err()?;
+    return Ok(pkcs7_certificate(&bytes).map(<[u8]>::to_vec))
+}
+
+/// ContentInfo { signedData OID, [0] SignedData { version, digestAlgorithms, contentInfo, [0] certificates, … } }
+fn pkcs7_certificate(der: &[u8]) -> Option<&[u8]> {
+    let (_, content_info, _) = der_item(der).filter(|(tag, _, _)| *tag == DER_SEQUENCE)?;
+    let (_, _, rest) = der_item(content_info).filter(|(tag, _, _)| *tag == DER_OID)?;
+    let (_, explicit, _) = der_item(rest).filter(|(tag, _, _)| *tag == DER_CONTEXT_0)?;
+    let (_, mut signed_data, _) = der_item(explicit).filter(|(tag, _, _)| *tag == DER_SEQUENCE)?;
+    while !signed_data.is_empty() {
+        let (tag, content, rest) = der_item(signed_data)?;
+        if tag == DER_CONTEXT_0 {
+            // the whole first certificate with its header
+            let (_, _, after) = der_item(content)?;
+            return Some(&content[..content.len() - after.len()])
+        }
+        signed_data = rest;
+    }
+    return None
+}
+
+/// The tag, the content and the bytes after the item.
+fn der_item(bytes: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *bytes.first()?;
+    let first = *bytes.get(1)? as usize;
+    let (length, header) = match first & 0x80 != 0 {
+        false => (first, 2),
+        true => {
+            let count = first & 0x7F;
+            if count == 0 || count > 4 {
+                return None
+            }
+            let length = bytes.get(2..2 + count)?
+                .iter()
+                .fold(0usize, |length, it| length << 8 | *it as usize);
+            (length, 2 + count)
+        },
+    };
+    let end = header.checked_add(length)?;
+    return Some((tag, bytes.get(header..end)?, &bytes[end..]))
+}
+
+fn prefixed(bytes: &[u8], offset: usize) -> Option<(&[u8], usize)> {
+    let length = u32_at(bytes, offset)? as usize;
+    let start = offset + 4;
+    let end = start.checked_add(length)?;
+    return Some((bytes.get(start..end)?, end))
+}
+
+fn read_at(file: &mut File, offset: u64, length: u64) -> Option<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut bytes = vec![0; length as usize];
+    file.read_exact(&mut bytes).ok()?;
+    return Some(bytes)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    return Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,110 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::axml::Manifest;
+use crate::core::ext::OutputExt;
+use crate::core::r#const::SHELL;
+use crate::core::selector::run_adb_with;
+use std::path::PathBuf;
+
+const GET_ABI_LIST: &str = "getprop ro.product.cpu.abilist";
+const GET_ABI: &str = "getprop ro.product.cpu.abi";
+const WM_DENSITY: &str = "wm density";
+const GET_LOCALES: &str = "getprop persist.sys.locale; getprop ro.product.locale";
+const CONFIG: &str = "config.";
+const ABIS: &[&str] = &["armeabi", "armeabi_v7a", "arm64_v8a", "x86", "x86_64", "mips", "mips64"];
+const DENSITIES: &[(&str, u32)] = &[
+    ("ldpi", 120), ("mdpi", 160), ("tvdpi", 213), ("hdpi", 240),
+    ("xhdpi", 320), ("xxhdpi", 480), ("xxxhdpi", 640),
+];
+
+/// What the config splits of an app are chosen by.
+pub struct DeviceSpec {
+    /// preferred first, with `_` as in the split names
+    pub abis: Vec<String>,
+    pub density: Option<u32>,
+    pub languages: Vec<String>,
+}
+
+impl DeviceSpec {
+    pub fn fetch(device: &AdbDevice) -> DeviceSpec {
+        let shell = |command: &str| run_adb_with(device, AdbArgs::run(&[SHELL, command])).stdout();
+        let mut abis = shell(GET_ABI_LIST);
+        if abis.is_empty() {
+            abis = shell(GET_ABI);
+        }
+        let abis = abis.split(',')
+            .map(|it| it.trim().replace('-', "_"))
+            .filter(|it| !it.is_empty())
+            .collect();
+        // "Physical density: 420" and optionally "Override density: 480" after it
+        let density = shell(WM_DENSITY)
+            .lines()
+            .filter_map(|it| it.rsplit_once(':'))
+            .filter_map(|(_, value)| value.trim().parse().ok())
+            .next_back();
+        let languages = shell(GET_LOCALES)
+            .lines()
+            .filter_map(|it| it.split(['-', '_']).next())
+            .map(|it| it.trim().to_lowercase())
+            .filter(|it| !it.is_empty())
+            .collect();
+        return DeviceSpec { abis, density, languages }
+    }
+
+    /// Keeps the base, the feature splits and the config splits matching the device,
+    /// for the ABI and density only the best one of the available.
+    pub fn select(&self, apks: Vec<(PathBuf, Manifest)>) -> Vec<(PathBuf, Manifest)> {
+        let qualifiers = apks.iter()
+            .filter_map(|(_, it)| qualifier(it))
+            .collect::<Vec<_>>();
+        let abi = self.abis.iter()
+            .find(|abi| qualifiers.contains(&abi.as_str()))
+            .cloned();
+        let densities = qualifiers.iter()
+            .filter_map(|it| density_of(it))
+            .collect::<Vec<_>>();
+        let density = match self.density {
+            None => densities.iter().max().copied(),
+            Some(device) => densities.iter()
+                .filter(|it| **it >= device)
+                .min()
+                .or(densities.iter().max())
+                .copied(),
+        };
+        let matches = |qualifier: &str| match () {
+            _ if ABIS.contains(&qualifier) => abi.as_deref() == Some(qualifier),
+            _ if density_of(qualifier).is_some() => density_of(qualifier) == density,
+            _ if is_language(qualifier) => self.languages.iter().any(|it| it == language_of(qualifier)),
+            _ => true,
+        };
+        return apks.into_iter()
+            .filter(|(_, manifest)| qualifier(manifest).map(matches).unwrap_or(true))
+            .collect()
+    }
+}
+
+fn qualifier(manifest: &Manifest) -> Option<&str> {
+    manifest.split.as_deref().and_then(config_qualifier)
+}
+
+/// "config.arm64_v8a" and "feature.config.xxhdpi" give the part after "config."
+fn config_qualifier(split: &str) -> Option<&str> {
+    split.split_once(CONFIG)
+        .filter(|(prefix, _)| prefix.is_empty() || prefix.ends_with('.'))
+        .map(|(_, qualifier)| qualifier)
+}
+
+fn density_of(qualifier: &str) -> Option<u32> {
+    DENSITIES.iter()
+        .find(|(name, _)| *name == qualifier)
+        .map(|(_, dpi)| *dpi)
+}
+
+fn language_of(qualifier: &str) -> &str {
+    qualifier.split(['_', '-']).next().unwrap_or_default()
+}
+
+fn is_language(qualifier: &str) -> bool {
+    let language = language_of(qualifier);
+    return (2..=3).contains(&language.len()) && language.chars().all(|it| it.is_ascii_lowercase())
+}
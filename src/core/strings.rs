@@ -46,17 +46,37 @@ pub static NO_ADB: Label = Label::new(
     "aDB wasn't recognized",
     "aDB не обнаружен",
 );
-pub static NO_ANDROID_SDK: Label = Label::new(
-    "specify a path to the Android SDK or Build Tools in {}",
-    "укажите путь до Android SDK или Build Tools в {}",
-);
 pub static NO_SUCH_DIRECTORY: Label = Label::new(
     "no such directory",
     "такой папки не существует",
 );
-pub static NO_BUILD_TOOLS: Label = Label::new(
-    "no build tools found in {}",
-    "build tools не найдены в {}",
+pub static INVALID_MANIFEST: Label = Label::new(
+    "AndroidManifest.xml is missing or broken",
+    "AndroidManifest.xml отсутствует или повреждён",
+);
+pub static NO_APKS: Label = Label::new(
+    "no APK found",
+    "APK не найдены",
+);
+pub static NO_BUNDLETOOL: Label = Label::new(
+    "specify a path to bundletool.jar as environment.bundletool in {}",
+    "укажите путь до bundletool.jar как environment.bundletool в {}",
+);
+pub static NO_JAVA: Label = Label::new(
+    "java not found, add it to PATH or set JAVA_HOME",
+    "java не найдена, добавьте её в PATH или задайте JAVA_HOME",
+);
+pub static BUILDING_APKS: Label = Label::new(
+    "building APKs of {}",
+    "сборка APK из {}",
+);
+pub static LAUNCH_TIMES: Label = Label::new(
+    "TotalTime: {} ms, WaitTime: {} ms",
+    "TotalTime: {} мс, WaitTime: {} мс",
+);
+pub static NO_LAUNCHER_ACTIVITY: Label = Label::new(
+    "{} has no launcher activity",
+    "у {} нет активности для запуска",
 );
 pub static NO_FILE: Label = Label::new(
     "no such file",
@@ -70,22 +90,118 @@ pub static SELECT_DEVICE: Label = Label::new(
     "select a device",
     "выберите устройство",
 );
-pub static SELECT_ANIM_SCALE: Label = Label::new(
-    "select the scale",
-    "выберите множитель",
+pub static SELECT_VALUE: Label = Label::new(
+    "select the value",
+    "выберите значение",
+);
+pub static UNKNOWN_VALUE: Label = Label::new(
+    "unknown value: {}, expected one of {}",
+    "неизвестное значение: {}, ожидается одно из {}",
 );
 pub static MEDIAS_NOT_FOUND: Label = Label::new(
     "screenshots/casts were not found",
     "скриншоты/записи не найдены",
 );
-pub static NO_PACKAGE_NAME: Label = Label::new(
-    "no package name passed",
-    "не указано имя пакета",
+pub static NOT_INSTALLED: Label = Label::new(
+    "{} is not installed",
+    "{} не установлен",
+);
+pub static VERSION_NAME: Label = Label::new(
+    "version: {}",
+    "версия: {}",
+);
+pub static SELECT_PACKAGE: Label = Label::new(
+    "select a package",
+    "выберите пакет",
+);
+pub static NO_PACKAGES: Label = Label::new(
+    "no packages found",
+    "пакеты не найдены",
+);
+pub static KILLED: Label = Label::new(
+    "{} killed",
+    "{} убит",
+);
+pub static DATA_CLEARED: Label = Label::new(
+    "data of {} cleared",
+    "данные {} очищены",
+);
+pub static RESTARTED: Label = Label::new(
+    "{} restarted",
+    "{} перезапущен",
+);
+pub static STOPPED: Label = Label::new(
+    "{} stopped",
+    "{} остановлен",
+);
+pub static UNINSTALLED: Label = Label::new(
+    "{} uninstalled",
+    "{} удалён",
+);
+pub static CLEAR_DATA_OF: Label = Label::new(
+    "clear the data of {}?",
+    "очистить данные {}?",
+);
+pub static UNINSTALL_PACKAGE: Label = Label::new(
+    "uninstall {}?",
+    "удалить {}?",
+);
+pub static NOT_DEBUGGABLE: Label = Label::new(
+    "{} is not debuggable, run-as can't access its data",
+    "{} не debuggable, run-as не имеет доступа к его данным",
+);
+pub static DATA_RESTORED: Label = Label::new(
+    "data of {} restored",
+    "данные {} восстановлены",
+);
+pub static USAGE: Label = Label::new(
+    "usage: {}",
+    "использование: {}",
+);
+pub static SELECT_PERMISSION: Label = Label::new(
+    "select a permission",
+    "выберите разрешение",
+);
+pub static NO_PERMISSIONS: Label = Label::new(
+    "no permissions found",
+    "разрешения не найдены",
+);
+pub static PERMISSION_GRANTED: Label = Label::new(
+    "{} granted",
+    "{} выдано",
+);
+pub static PERMISSION_REVOKED: Label = Label::new(
+    "{} revoked",
+    "{} отозвано",
+);
+pub static PERMISSIONS_RESET: Label = Label::new(
+    "cleared the flags of {} permissions of {}",
+    "сброшены флаги {} разрешений {}",
+);
+pub static WATCHING: Label = Label::new(
+    "watching {}, press Ctrl-C to stop",
+    "слежу за {}, нажмите Ctrl-C для остановки",
+);
+pub static CHANGED: Label = Label::new(
+    "{} changed",
+    "{} изменён",
+);
+pub static TIMINGS: Label = Label::new(
+    "installed in {} s, launched in {} s",
+    "установлено за {} с, запущено за {} с",
 );
 pub static PRESS_ENTER_TO_STOP_REC: Label = Label::new(
     "press Enter to stop recording",
     "нажмите Enter, чтобы остановить запись",
 );
+pub static PRESS_ENTER_TO_STOP_CAPTURE: Label = Label::new(
+    "press Enter to stop capturing",
+    "нажмите Enter, чтобы остановить съёмку",
+);
+pub static CAPTURES_FAILED: Label = Label::new(
+    "{} of {} screenshots failed",
+    "не удалось снять {} из {} скриншотов",
+);
 pub static UNAUTHORIZED_BY_DEVICE: Label = Label::new(
     "unauthorized by the device",
     "на устройстве не дано разрешение",
@@ -104,6 +220,42 @@ pub static ADD_INTERPRETER: Label = Label::new(
     "add the interpreter into the hook file, for example #!/bin/bash or #!/bin/zsh",
     "добавьте интерпретатор в файл хука, например #!/bin/bash или #!/bin/zsh",
 );
+pub static UNKNOWN_OPTION: Label = Label::new(
+    "unknown option {}",
+    "неизвестный параметр {}",
+);
+pub static NO_OPTION_VALUE: Label = Label::new(
+    "no value for the option {}",
+    "не указано значение параметра {}",
+);
+pub static INVALID_OPTION_VALUE: Label = Label::new(
+    "invalid value of the option {}: {}",
+    "недопустимое значение параметра {}: {}",
+);
+pub static OPTION_REQUIRES_SDK: Label = Label::new(
+    "the option {} requires Android API {} or higher, the device has {}",
+    "параметр {} требует Android API {} или выше, на устройстве {}",
+);
+pub static NOT_RUNNING: Label = Label::new(
+    "{} is not running",
+    "{} не запущен",
+);
+pub static NO_FFMPEG: Label = Label::new(
+    "ffmpeg wasn't found, install it or add it into PATH",
+    "ffmpeg не найден, установите его или добавьте в PATH",
+);
+pub static CONVERTING: Label = Label::new(
+    "converting into {}",
+    "конвертация в {}",
+);
+pub static HOOK_TIMED_OUT: Label = Label::new(
+    "the hook {} timed out",
+    "хук {} не завершился вовремя",
+);
+pub static UNKNOWN_PLACEHOLDER: Label = Label::new(
+    "unknown placeholder {} in {}",
+    "неизвестный плейсхолдер {} в {}",
+);
 pub static INPUT_OR_EXIT: Label = Label::new(
     "input command or 'exit', 'quit', Ctrl-D to exit",
     "введите команду или 'exit', 'quit', Ctrl-D, чтобы выйти",
@@ -133,6 +285,230 @@ pub static ERROR: Label = Label::new(
     "error",
     "ошибка",
 );
+pub static INSTALL_UPDATE_INCOMPATIBLE: Label = Label::new(
+    "the installed app is signed with another key",
+    "установленное приложение подписано другим ключом",
+);
+pub static INSTALL_VERSION_DOWNGRADE: Label = Label::new(
+    "the installed app has a higher versionCode",
+    "у установленного приложения versionCode выше",
+);
+pub static INSTALL_TEST_ONLY: Label = Label::new(
+    "the APK is marked as testOnly, Android Studio builds it for the Run button",
+    "APK помечен как testOnly, Android Studio собирает такие для кнопки Run",
+);
+pub static INSTALL_INSUFFICIENT_STORAGE: Label = Label::new(
+    "not enough free space on the device",
+    "недостаточно свободного места на устройстве",
+);
+pub static INSTALL_OLDER_SDK: Label = Label::new(
+    "the device is older than minSdkVersion of the app",
+    "устройство старше, чем minSdkVersion приложения",
+);
+pub static INSTALL_DEPRECATED_SDK: Label = Label::new(
+    "targetSdkVersion of the app is too low for this Android",
+    "targetSdkVersion приложения слишком низкий для этого Android",
+);
+pub static INSTALL_NO_MATCHING_ABIS: Label = Label::new(
+    "the native libraries of the app don't support the CPU of the device",
+    "нативные библиотеки приложения не поддерживают процессор устройства",
+);
+pub static INSTALL_MISSING_SHARED_LIBRARY: Label = Label::new(
+    "the app needs a library which the device doesn't have",
+    "приложению нужна библиотека, которой нет на устройстве",
+);
+pub static INSTALL_DUPLICATE_PERMISSION: Label = Label::new(
+    "another app already defines a permission of this app",
+    "другое приложение уже объявляет разрешение этого приложения",
+);
+pub static INSTALL_USER_RESTRICTED: Label = Label::new(
+    "the installation via USB is disabled or was declined on the device",
+    "установка через USB отключена или отклонена на устройстве",
+);
+pub static INSTALL_ABORTED: Label = Label::new(
+    "the installation was cancelled on the device",
+    "установка отменена на устройстве",
+);
+pub static INSTALL_NO_CERTIFICATES: Label = Label::new(
+    "the APK isn't signed",
+    "APK не подписан",
+);
+pub static INSTALL_PARSE_FAILED: Label = Label::new(
+    "the APK is broken or can't be read by this Android",
+    "APK повреждён или не читается этим Android",
+);
+pub static UNINSTALL_AND_RETRY: Label = Label::new(
+    "uninstall the app (its data will be lost) and retry?",
+    "удалить приложение (его данные будут потеряны) и повторить?",
+);
+pub static RETRY_DOWNGRADE: Label = Label::new(
+    "retry with -d to allow the downgrade?",
+    "повторить с -d, чтобы разрешить понижение версии?",
+);
+pub static RETRY_TEST_ONLY: Label = Label::new(
+    "retry with -t to allow the test APK?",
+    "повторить с -t, чтобы разрешить тестовый APK?",
+);
+pub static FREE_SPACE: Label = Label::new(
+    "free on /data: {}",
+    "свободно на /data: {}",
+);
+pub static APK_PACKAGE: Label = Label::new(
+    "package: {}",
+    "пакет: {}",
+);
+pub static APK_VERSION: Label = Label::new(
+    "version: {} ({})",
+    "версия: {} ({})",
+);
+pub static APK_SDK: Label = Label::new(
+    "SDK: min {}, target {}, compile {}",
+    "SDK: минимальный {}, целевой {}, компиляции {}",
+);
+pub static APK_ABIS: Label = Label::new(
+    "ABIs: {}",
+    "ABI: {}",
+);
+pub static APK_SIGNATURE: Label = Label::new(
+    "signature {}, SHA-256: {}",
+    "подпись {}, SHA-256: {}",
+);
+pub static APK_UNSIGNED: Label = Label::new(
+    "no signature",
+    "подписи нет",
+);
+pub static APK_LAUNCHERS: Label = Label::new(
+    "launcher activities:",
+    "активности для запуска:",
+);
+pub static APK_PERMISSIONS: Label = Label::new(
+    "permissions:",
+    "разрешения:",
+);
+pub static APK_INSTALLED: Label = Label::new(
+    "installed: {} ({})",
+    "установлено: {} ({})",
+);
+pub static SIGNATURE_MATCHES: Label = Label::new(
+    "the signature matches the installed app",
+    "подпись совпадает с установленным приложением",
+);
+pub static SIGNATURE_DIFFERS: Label = Label::new(
+    "the signature differs from the installed app, SHA-256: {}",
+    "подпись отличается от установленного приложения, SHA-256: {}",
+);
+pub static BOTH_UNSIGNED: Label = Label::new(
+    "neither the APK nor the installed app is signed",
+    "ни APK, ни установленное приложение не подписаны",
+);
+pub static INSTALLED_UNSIGNED: Label = Label::new(
+    "the installed app isn't signed",
+    "установленное приложение не подписано",
+);
+pub static APK_NEWER: Label = Label::new(
+    "the APK is newer than the installed app",
+    "APK новее установленного приложения",
+);
+pub static APK_SAME_VERSION: Label = Label::new(
+    "the APK has the same version code as the installed app",
+    "у APK тот же код версии, что у установленного приложения",
+);
+pub static APK_OLDER: Label = Label::new(
+    "the APK is older than the installed app, installing it is a downgrade",
+    "APK старше установленного приложения, его установка понизит версию",
+);
+pub static DEVICE_NOT_FOUND: Label = Label::new(
+    "no connected device {}",
+    "нет подключённого устройства {}",
+);
+pub static APP_CLONED: Label = Label::new(
+    "{} cloned from {} to {}",
+    "{} скопирован с {} на {}",
+);
+pub static APPS_EXPORTED: Label = Label::new(
+    "{} of {} apps exported",
+    "экспортировано приложений: {} из {}",
+);
+pub static APPS_IMPORTED: Label = Label::new(
+    "installed: {}, skipped: {}, failed: {}",
+    "установлено: {}, пропущено: {}, с ошибкой: {}",
+);
+pub static APP_SKIPPED: Label = Label::new(
+    "skipped, the installed versionCode is {}",
+    "пропущено, установлен versionCode {}",
+);
+pub static LOG_WAITING: Label = Label::new(
+    "{} is not running, waiting for it to start",
+    "{} не запущен, ожидание запуска",
+);
+pub static LOG_PROCESS_STARTED: Label = Label::new(
+    "--- {} started, pid {}",
+    "--- {} запущен, pid {}",
+);
+pub static LOG_PROCESS_DIED: Label = Label::new(
+    "--- {} died, pid {}",
+    "--- {} завершён, pid {}",
+);
+pub static UNKNOWN_LEVEL: Label = Label::new(
+    "unknown level: {}, expected one of V, D, I, W, E, F",
+    "неизвестный уровень: {}, ожидается один из V, D, I, W, E, F",
+);
+pub static CRASH_WATCHING: Label = Label::new(
+    "watching for crashes and ANRs of {}, press Ctrl-C to stop",
+    "слежу за падениями и ANR {}, нажмите Ctrl-C для остановки",
+);
+pub static CRASH_WATCHING_ALL: Label = Label::new(
+    "watching for crashes and ANRs, press Ctrl-C to stop",
+    "слежу за падениями и ANR, нажмите Ctrl-C для остановки",
+);
+pub static CRASH_DETECTED: Label = Label::new(
+    "{}: {}, pid {}",
+    "{}: {}, pid {}",
+);
+pub static KIND_CRASH: Label = Label::new(
+    "crash",
+    "падение",
+);
+pub static KIND_NATIVE_CRASH: Label = Label::new(
+    "native crash",
+    "нативное падение",
+);
+pub static KIND_ANR: Label = Label::new(
+    "ANR",
+    "ANR",
+);
+pub static PROFILE_SAVED: Label = Label::new(
+    "profile {} saved",
+    "профиль {} сохранён",
+);
+pub static PROFILE_APPLIED: Label = Label::new(
+    "profile {} applied, `profile restore` brings back the previous values",
+    "профиль {} применён, `profile restore` вернёт прежние значения",
+);
+pub static PROFILE_RESTORED: Label = Label::new(
+    "the values before the profile are restored",
+    "значения до применения профиля восстановлены",
+);
+pub static NO_PROFILE: Label = Label::new(
+    "no profile {}, the saved ones: {}",
+    "нет профиля {}, сохранённые: {}",
+);
+pub static NO_PROFILE_BACKUP: Label = Label::new(
+    "no profile was applied on this device",
+    "на этом устройстве профиль не применялся",
+);
+pub static UNKNOWN_SETTING: Label = Label::new(
+    "unknown setting: {}",
+    "неизвестная настройка: {}",
+);
+pub static SETTINGS_NOT_READ: Label = Label::new(
+    "can't read the settings: {}",
+    "не удалось прочитать настройки: {}",
+);
+pub static SETTINGS_NOT_SET: Label = Label::new(
+    "can't set the settings: {}",
+    "не удалось изменить настройки: {}",
+);
 
 pub enum Language {
     En,
@@ -14,6 +14,8 @@ use std::os::unix::process::ExitStatusExt;
 use std::os::windows::process::ExitStatusExt;
 use std::path::PathBuf;
 use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use std::{fs, io};
 
 pub const ADB_EXT: &str = "adb-ext";
@@ -36,6 +38,8 @@ pub const PROGRAMS: &str = "Programs";
 #[cfg(windows)]
 pub const PATH: &str = "PATH";
 
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
 #[cfg(unix)]
 pub fn interrupt(id: u32) {
     let pid = nix::unistd::Pid::from_raw(id as nix::libc::pid_t);
@@ -61,6 +65,110 @@ pub fn interrupt(id: u32) {
     }
 }
 
+/// Ctrl-C sets the flag of `interrupted` instead of killing the process until `release_interrupts`.
+#[cfg(unix)]
+pub fn catch_interrupts() {
+    use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+    extern "C" fn on_interrupt(_: nix::libc::c_int) {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+    }
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    let action = SigAction::new(SigHandler::Handler(on_interrupt), SaFlags::empty(), SigSet::empty());
+    unsafe { sigaction(Signal::SIGINT, &action) }.unwrap();
+}
+
+#[cfg(unix)]
+pub fn release_interrupts() {
+    use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+    let action = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    unsafe { sigaction(Signal::SIGINT, &action) }.unwrap();
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn on_interrupt(_: u32) -> i32 {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    return 1
+}
+
+/// Ctrl-C sets the flag of `interrupted` instead of killing the process until `release_interrupts`.
+#[cfg(windows)]
+pub fn catch_interrupts() {
+    use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    unsafe { SetConsoleCtrlHandler(Some(on_interrupt), 1) };
+}
+
+#[cfg(windows)]
+pub fn release_interrupts() {
+    use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+    unsafe { SetConsoleCtrlHandler(Some(on_interrupt), 0) };
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Waits up to `timeout` for a line on stdin.
+#[cfg(unix)]
+pub fn enter_pressed(timeout: Duration) -> bool {
+    use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+    use std::os::fd::AsFd;
+    let stdin = io::stdin();
+    let mut fds = [PollFd::new(stdin.as_fd(), PollFlags::POLLIN)];
+    let poll_timeout = PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX);
+    let start = std::time::Instant::now();
+    if !matches!(poll(&mut fds, poll_timeout), Ok(count) if count > 0) {
+        return false
+    }
+    return match stdin.read_line(&mut String::new()) {
+        Ok(count) if count > 0 => true,
+        _ => {
+            // closed stdin is always ready, don't spin on it
+            std::thread::sleep(timeout.saturating_sub(start.elapsed()));
+            false
+        },
+    }
+}
+
+/// Waits up to `timeout` for Enter on the console, the other input events are dropped.
+#[cfg(windows)]
+pub fn enter_pressed(timeout: Duration) -> bool {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::WAIT_OBJECT_0;
+    use windows_sys::Win32::System::Console::{ReadConsoleInputW, INPUT_RECORD, KEY_EVENT};
+    use windows_sys::Win32::System::Threading::WaitForSingleObject;
+    const VK_RETURN: u16 = 0x0D;
+    let stdin = io::stdin();
+    let handle = stdin.as_raw_handle();
+    let start = std::time::Instant::now();
+    loop {
+        let left = timeout.saturating_sub(start.elapsed());
+        let millis = left.as_millis().min(u32::MAX as u128 - 1) as u32;
+        // the console is signaled by the key ups, the mouse, the focus and the resizing as well
+        if unsafe { WaitForSingleObject(handle, millis) } != WAIT_OBJECT_0 {
+            return false
+        }
+        let mut record = INPUT_RECORD::default();
+        let mut count = 0;
+        if unsafe { ReadConsoleInputW(handle, &mut record, 1, &mut count) } == 0 {
+            // not a console, a redirected stdin is read by lines
+            return match stdin.read_line(&mut String::new()) {
+                Ok(count) if count > 0 => true,
+                _ => {
+                    std::thread::sleep(timeout.saturating_sub(start.elapsed()));
+                    false
+                },
+            }
+        }
+        if count == 1 && record.EventType == KEY_EVENT as u16 {
+            let key = unsafe { record.Event.KeyEvent };
+            if key.bKeyDown != 0 && key.wVirtualKeyCode == VK_RETURN {
+                return true
+            }
+        }
+    }
+}
+
 #[cfg(unix)]
 pub fn make_executable(path: PathBuf) -> io::Result<PathBuf> {
     let mut perms = fs::metadata(&path)?.permissions();
@@ -0,0 +1,130 @@
+use crate::core::activity::{focused_package, rotation};
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::Config;
+use crate::core::destination::Destination;
+use crate::core::ext::PathBufExt;
+use crate::core::r#const::{FLAND, FPORT, LAND, PORT};
+use crate::core::strings::UNKNOWN;
+use crate::core::util::{format_file_name, string};
+use regex::Regex;
+use std::cell::OnceCell;
+use std::path::PathBuf;
+
+const MODEL: &str = "model";
+const ALIAS: &str = "alias";
+const SERIAL: &str = "serial";
+const SDK: &str = "sdk";
+const PACKAGE: &str = "package";
+const ORIENTATION: &str = "orientation";
+const N: &str = "n";
+pub const PLACEHOLDERS: [&str; 7] = [MODEL, ALIAS, SERIAL, SDK, PACKAGE, ORIENTATION, N];
+const PLACEHOLDER: &str = r"\{(\w*)\}";
+const N_PLACEHOLDER: &str = "{n}";
+
+/// Expands the device-aware placeholders like {model} in file names and destinations,
+/// the values which require extra adb calls are fetched once on demand.
+pub struct Template<'a> {
+    device: &'a AdbDevice,
+    alias: Option<String>,
+    package: OnceCell<String>,
+    orientation: OnceCell<String>,
+}
+
+impl Template<'_> {
+    pub fn new<'a>(device: &'a AdbDevice, config: &Config) -> Template<'a> {
+        Template {
+            device,
+            alias: config.aliases.get(&device.serial).cloned(),
+            package: OnceCell::new(),
+            orientation: OnceCell::new(),
+        }
+    }
+
+    /// Resolves the destination and the chrono-formatted name, {n} takes the first free number.
+    pub fn file(&self, dst: &str, default_parent: &str, name: &str) -> PathBuf {
+        let name = format_file_name(&self.expand(name, true));
+        return numbered(self.path(dst, default_parent).join(name))
+    }
+
+    /// Resolves the destination without the {n} placeholder.
+    pub fn path(&self, dst: &str, default_parent: &str) -> PathBuf {
+        self.expand(dst, false)
+            .dst_with_parent(&self.expand(default_parent, false))
+    }
+
+    /// Resolves the destination directory.
+    pub fn dir(&self, dst: &str) -> PathBuf {
+        numbered(self.expand(dst, false).dst())
+    }
+
+    fn expand(&self, value: &str, escape: bool) -> String {
+        Regex::new(PLACEHOLDER).unwrap().replace_all(value, |captures: &regex::Captures| {
+            let value = match &captures[1] {
+                N => return string(N_PLACEHOLDER),
+                name => self.value(name).unwrap_or(captures[0].to_string()),
+            };
+            let value = sanitize(&value);
+            match escape {
+                true => value.replace('%', "%%"),
+                false => value,
+            }
+        }).to_string()
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        let value = match name {
+            MODEL => self.device.model.clone(),
+            ALIAS => self.alias.clone().unwrap_or(self.device.model.clone()),
+            SERIAL => self.device.serial.clone(),
+            SDK => self.device.sdk.map(|it| it.to_string()).unwrap_or(UNKNOWN.value().to_string()),
+            PACKAGE => self.package.get_or_init(|| {
+                focused_package(self.device)
+                    .unwrap_or(UNKNOWN.value().to_string())
+            }).clone(),
+            ORIENTATION => self.orientation.get_or_init(|| {
+                let name = match rotation(self.device) {
+                    Some(0) => PORT,
+                    Some(1) => LAND,
+                    Some(2) => FPORT,
+                    Some(3) => FLAND,
+                    _ => UNKNOWN.value(),
+                };
+                string(name)
+            }).clone(),
+            _ => return None,
+        };
+        return Some(value)
+    }
+}
+
+pub fn numbered(path: PathBuf) -> PathBuf {
+    let path = path.to_string();
+    if !path.contains(N_PLACEHOLDER) {
+        return path.into()
+    }
+    let mut n = 1;
+    loop {
+        let candidate = PathBuf::from(path.replace(N_PLACEHOLDER, &n.to_string()));
+        if !candidate.exists() {
+            return candidate
+        }
+        n += 1;
+    }
+}
+
+fn sanitize(value: &str) -> String {
+    value.chars()
+        .map(|c| if c.is_alphanumeric() || " .,-_()+".contains(c) { c } else { '_' })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The placeholders of the value which can't be expanded.
+pub fn unknown_placeholders(value: &str) -> Vec<String> {
+    Regex::new(PLACEHOLDER).unwrap()
+        .captures_iter(value)
+        .filter(|it| !PLACEHOLDERS.contains(&&it[1]))
+        .map(|it| it[0].to_string())
+        .collect()
+}
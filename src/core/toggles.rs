@@ -0,0 +1,139 @@
+use crate::core::adb_command::AdbArgs;
+use crate::core::adb_device::AdbDevice;
+use crate::core::config::{Config, Toggle};
+use crate::core::ext::{OutputExt, PrintExt};
+use crate::core::r#const::{ANI_SCALE, BOUNDS, OFF, ON, POINTER, SHELL, TAPS};
+use crate::core::selector::{resolve_device, run_adb_with};
+use crate::core::strings::{SELECT_VALUE, UNKNOWN_VALUE};
+use crate::core::util::{interactive_select, string};
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+const GET_TOUCHES: &str = "settings get system show_touches";
+const PUT_TOUCHES: &str = "settings put system show_touches";
+const GET_POINTER: &str = "settings get system pointer_location";
+const PUT_POINTER: &str = "settings put system pointer_location";
+const GET_LAYOUT: &str = "getprop debug.layout";
+const SET_LAYOUT: &str = "setprop debug.layout";
+const PUT_LAYOUT: &str = "settings put global debug_layout";
+// makes the apps pick up the changed system properties
+pub const CALL: &str = "service call activity 1599295570";
+const GET_SCALE: &str = "settings get global animator_duration_scale";
+const PUT_SCALES: &[&str] = &[
+    "settings put global window_animation_scale",
+    "settings put global transition_animation_scale",
+    "settings put global animator_duration_scale",
+];
+const SCALES: &[&str] = &["0", "0.5", "1", "1.5", "2", "5", "10"];
+const TRUE: &str = "true";
+const FALSE: &str = "false";
+
+/// The toggles which are there unless the config redefines them.
+pub fn builtin_toggles() -> BTreeMap<String, Toggle> {
+    let switch = |get: &str, put: &str| Toggle {
+        get: string(get),
+        set: BTreeMap::from([(string(OFF), format!("{put} {OFF}")), (string(ON), format!("{put} {ON}"))]),
+        post: None,
+        symlink: true,
+    };
+    let bounds = Toggle {
+        get: string(GET_LAYOUT),
+        set: [FALSE, TRUE].iter()
+            .map(|it| (string(it), format!("{SET_LAYOUT} {it} && {PUT_LAYOUT} {it}")))
+            .collect(),
+        post: Some(string(CALL)),
+        symlink: true,
+    };
+    let scales = Toggle {
+        get: string(GET_SCALE),
+        set: SCALES.iter()
+            .map(|scale| (string(scale), PUT_SCALES.iter().map(|it| format!("{it} {scale}")).collect::<Vec<_>>().join(" && ")))
+            .collect(),
+        post: None,
+        symlink: true,
+    };
+    return BTreeMap::from([
+        (string(TAPS), switch(GET_TOUCHES, PUT_TOUCHES)),
+        (string(POINTER), switch(GET_POINTER, PUT_POINTER)),
+        (string(BOUNDS), bounds),
+        (string(ANI_SCALE), scales),
+    ])
+}
+
+/// A toggle command: sets the given value, flips a two-valued toggle or asks for the value.
+pub fn toggle(name: &str, value: Option<String>, config: &Config) -> ExitCode {
+    let toggle = &config.toggles[name];
+    let device = match resolve_device() {
+        Ok(device) => device,
+        Err(code) => return code,
+    };
+    let values = toggle.values();
+    let value = match value {
+        Some(value) => match values.iter().find(|it| same(it, &value)) {
+            Some(value) => value.to_string(),
+            None => {
+                UNKNOWN_VALUE.formatted(&[&value, &values.join(", ")]).eprintln();
+                return ExitCode::FAILURE
+            }
+        },
+        None if values.len() == 2 => {
+            // an unset value counts as the first one
+            let current = toggle.value(&device);
+            let index = values.iter().position(|it| same(it, &current)).unwrap_or(0);
+            values[(index + 1) % values.len()].to_string()
+        },
+        None => match interactive_select(SELECT_VALUE.value(), values, |it, _| it.to_string()) {
+            Ok(value) => value.to_string(),
+            Err(code) => return code,
+        },
+    };
+    return toggle.set(&device, &value)
+}
+
+impl Toggle {
+    pub fn value(&self, device: &AdbDevice) -> String {
+        run_adb_with(device, AdbArgs::run(&[SHELL, self.get.as_str()])).stdout()
+    }
+
+    pub fn is_on(&self, device: &AdbDevice) -> bool {
+        self.value(device) == ON
+    }
+
+    /// Runs the command of the value and then the post command in one shell, a failed command stops it.
+    pub fn set(&self, device: &AdbDevice, value: &str) -> ExitCode {
+        let command = match self.set.iter().find(|(it, _)| same(it, value)) {
+            Some((_, command)) => command,
+            None => {
+                UNKNOWN_VALUE.formatted(&[value, &self.values().join(", ")]).eprintln();
+                return ExitCode::FAILURE
+            }
+        };
+        let command = match &self.post {
+            Some(post) => format!("{command} && {post}"),
+            None => command.clone(),
+        };
+        let output = run_adb_with(device, AdbArgs::run(&[SHELL, command.as_str()]));
+        if !output.status.success() {
+            output.print_err();
+        }
+        return output.exit_code()
+    }
+
+    /// The numbers go in the numeric order.
+    pub fn values(&self) -> Vec<&str> {
+        let mut values = self.set.keys().map(String::as_str).collect::<Vec<_>>();
+        values.sort_by(|a, b| match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.total_cmp(&b),
+            _ => a.cmp(b),
+        });
+        return values
+    }
+}
+
+/// The settings print "1.0" for "1".
+fn same(value: &str, other: &str) -> bool {
+    match (value.parse::<f64>(), other.parse::<f64>()) {
+        (Ok(value), Ok(other)) => value == other,
+        _ => value == other,
+    }
+}
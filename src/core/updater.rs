@@ -1,3 +1,4 @@
+use crate::core::config::Config;
 use crate::core::ext::PathBufExt;
 #[cfg(windows)]
 use crate::core::ext::StringExt;
@@ -92,7 +93,7 @@ fn download_with_progress(url: &str, dst: &PathBuf) -> Rslt<()> {
     Ok(())
 }
 
-pub fn deploy() -> ExitCode {
+pub fn deploy(config: &Config) -> ExitCode {
     let bin_dir = bin_dir();
     let bin_path = bin_path();
 
@@ -114,7 +115,10 @@ pub fn deploy() -> ExitCode {
     let src = env::args().nth(0).unwrap();
     fs::copy(src, &bin_path).unwrap();
     env::set_current_dir(&bin_dir).unwrap();
-    for link in [ADB, LSS, MSS, SHOT, LSC, MSC, REC, RECORD, BOUNDS, TAPS, POINTER, PORT, LAND, FPORT, FLAND, ACCEL, NO_ACCEL, ANI_SCALE, STEAL, RUN] {
+    let toggles = config.toggles.iter()
+        .filter(|(_, toggle)| toggle.symlink)
+        .map(|(name, _)| name.as_str());
+    for link in [ADB, LSS, MSS, SHOT, LSC, MSC, REC, RECORD, PORT, LAND, FPORT, FLAND, ACCEL, NO_ACCEL, STEAL, RUN].into_iter().chain(toggles) {
         let _ = remove_link(link);
         make_link(link).unwrap_or_else(|e|
             println!("{SYMLINK_FAIL}{link} ({e})")
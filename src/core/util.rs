@@ -1,12 +1,13 @@
-use crate::core::ext::{OutputExt, ResultExt, VecExt};
+use crate::core::ext::{ResultExt, VecExt};
 use crate::core::r#const::{HELP_TEXT, NULL};
 use crate::core::strings::CANCEL;
 use chrono::Local;
-use dialoguer::FuzzySelect;
+use dialoguer::{Confirm, FuzzySelect};
 use itertools::Itertools;
 use std::fs::create_dir_all;
-use std::path::PathBuf;
-use std::process::{Command, ExitCode};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 use crate::core::system::bin_name;
 
 pub fn get_help(separator: Option<&str>) -> String {
@@ -30,20 +31,29 @@ pub fn failure<T>() -> Result<T, ExitCode> {
     Err(ExitCode::FAILURE)
 }
 
-pub fn ensure_parent_exists(path: &PathBuf) {
+pub fn ensure_parent_exists(path: &Path) {
     let parent = path.parent().unwrap();
     create_dir_all(parent).unwrap();
 }
 
-pub fn try_run_hook_and_exit(hook: PathBuf, cmd: String, arg: PathBuf) -> ExitCode {
-    Command::new(hook).arg(cmd).arg(arg)
-        .spawn().unwrap()
-        .wait_with_output().unwrap()
-        .exit_code()
+pub fn format_file_name(name: &str) -> String {
+    Local::now().format(name).to_string()
 }
 
-pub fn format_file_name(name: &String) -> String {
-    Local::now().format(name).to_string()
+/// `500ms`, `10s`, `5m`, `1h`, a bare number means seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|it: char| !it.is_ascii_digit() && it != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number = number.parse::<f64>().ok().filter(|it| it.is_finite())?;
+    let millis = match unit {
+        "ms" => number,
+        "" | "s" => number * 1000.0,
+        "m" => number * 60_000.0,
+        "h" => number * 3_600_000.0,
+        _ => return None,
+    };
+    return Some(Duration::from_millis(millis as u64))
 }
 
 pub fn interactive_select<T, F: Fn(&T, &Vec<T>) -> String>(prompt: &str, mut items: Vec<T>, label: F) -> Result<T, ExitCode> {
@@ -65,3 +75,14 @@ pub fn interactive_select<T, F: Fn(&T, &Vec<T>) -> String>(prompt: &str, mut ite
     return VecExt::try_remove(&mut items, selection)
         .ok_or_else(|| ExitCode::SUCCESS) // cancel
 }
+
+/// Esc and an error mean no.
+pub fn interactive_confirm(prompt: &str) -> bool {
+    Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact_opt()
+        .soft_unwrap()
+        .flatten()
+        .unwrap_or(false)
+}
@@ -1,12 +1,21 @@
-use crate::core::anim_scale::change_anim_scale;
+use crate::core::apkinfo::apkinfo;
+use crate::core::appdata::appdata;
+use crate::core::apps::apps;
 use crate::core::apks::{run_apk, steal_apk};
+use crate::core::clone_app::clone_app;
 use crate::core::cmd_editor::{CmdEditor, CmdHelper, CmdHighlight};
 use crate::core::config::Config;
+use crate::core::crashwatch::crashwatch;
 use crate::core::ext::{PrintExt, ResultExt};
+use crate::core::demo::demo;
 use crate::core::fix::fix_on_linux;
-use crate::core::layout_bounds::debug_layout_bounds;
+use crate::core::launch::launch;
+use crate::core::lifecycle::{clear_data, kill, restart, stop, uninstall};
+use crate::core::logview::log;
+use crate::core::packages::{forget_packages, packages};
 use crate::core::orientation::{orientation, Orientation};
-use crate::core::pointer::toggle_pointer;
+use crate::core::perms::perms;
+use crate::core::profile::profile;
 use crate::core::pull_media::{pull_screencasts, pull_screenshots, Params};
 use crate::core::r#const::*;
 use crate::core::screencap::make_screenshot;
@@ -18,7 +27,7 @@ use crate::core::strings::{Language, INPUT_OR_EXIT};
 #[cfg(windows)]
 use crate::core::system::DOT_EXE;
 use crate::core::system::{history_path, ADB_EXT};
-use crate::core::taps::toggle_taps;
+use crate::core::toggles::toggle;
 use crate::core::updater::{deploy, update};
 use crate::core::util::{get_help, print_version, string};
 use rustyline::error::ReadlineError;
@@ -48,7 +57,8 @@ fn main() -> ExitCode {
         INPUT_OR_EXIT.println();
         let mut input = CmdEditor::new().unwrap();
         let success = Rc::new(RefCell::new(None));
-        let helper = CmdHelper::from(SUGGESTIONS, success.clone());
+        let toggles = config.toggles.keys().map(String::as_str);
+        let helper = CmdHelper::from(&SUGGESTIONS.iter().copied().chain(toggles).collect::<Vec<_>>(), success.clone());
         input.set_helper(Some(helper));
         let history_path = history_path();
         if history_path.exists() {
@@ -116,15 +126,33 @@ fn work(mode: StartMode, args: Vec<String>, config: &mut Config) -> ExitCode {
     let first = args.get(0)
         .unwrap_or(&string(""))
         .to_ascii_lowercase();
+    if PACKAGE_CHANGING_COMMANDS.contains(&first.as_str()) {
+        forget_packages();
+    }
     match first.as_str() {
         LSS => return pull_screenshots(Params::from(first, args.get(1).cloned()), config),
         LSC => return pull_screencasts(Params::from(first, args.get(1).cloned()), config),
-        MSS | SHOT => return make_screenshot(first, args.get(1).cloned().unwrap_or_default(), config),
-        MSC | REC | RECORD => return make_screencast(first, args.get(1).cloned().unwrap_or_default(), config),
+        MSS | SHOT => return make_screenshot(first, &args[1..], config),
+        MSC | REC | RECORD => return make_screencast(first, &args[1..], config),
         FIX => return fix_on_linux(args.get(1).cloned()),
-        RUN => return run_apk(args.get(1).cloned().unwrap_or_default(), config),
-        STEAL => return steal_apk(args.get(1).cloned(), args.get(2).cloned()),
-        DEPLOY => return deploy(),
+        RUN => return run_apk(&args[1..], config),
+        STEAL => return steal_apk(&args[1..]),
+        LAUNCH => return launch(&args[1..]),
+        PACKAGES => return packages(&args[1..]),
+        UNINSTALL if !mode.adb() => return uninstall(&args[1..]),
+        CLEAR => return clear_data(&args[1..]),
+        STOP | FORCE_STOP => return stop(&args[1..]),
+        KILL => return kill(&args[1..]),
+        APPDATA => return appdata(&args[1..]),
+        PERMS => return perms(&args[1..]),
+        APKINFO => return apkinfo(&args[1..]),
+        CLONE_APP => return clone_app(&args[1..]),
+        APPS => return apps(&args[1..]),
+        LOG => return log(&args[1..], config),
+        CRASHWATCH => return crashwatch(&args[1..], config),
+        PROFILE => return profile(&args[1..], config),
+        RESTART => return restart(&args[1..]),
+        DEPLOY => return deploy(config),
         UPDATE => return update(),
         PORT => return orientation(Orientation::portrait(false)),
         LAND => return orientation(Orientation::landscape(false)),
@@ -132,14 +160,12 @@ fn work(mode: StartMode, args: Vec<String>, config: &mut Config) -> ExitCode {
         FLAND => return orientation(Orientation::landscape(true)),
         ACCEL => return orientation(Orientation::accelerometer(true)),
         NO_ACCEL => return orientation(Orientation::accelerometer(false)),
-        BOUNDS => return debug_layout_bounds(),
-        TAPS => return toggle_taps(),
-        POINTER => return toggle_pointer(),
-        ANI_SCALE => return change_anim_scale(args.get(1).cloned().unwrap_or_default()),
+        DEMO => return demo(args.get(1).cloned(), config),
         SDK => return set_sdk(args.get(1).cloned(), config),
         VERSION if !mode.adb() => print_version(),
         HELP if !mode.adb() => get_help(None).println(),
         "shit" => "💩".println(),
+        name if config.toggles.contains_key(name) => return toggle(name, args.get(1).cloned(), config),
         _ => return resolve_device_and_run_args(args.as_slice()),
     };
     return ExitCode::SUCCESS
@@ -3,7 +3,22 @@ mod tests {
     use itertools::assert_equal;
     use crate::core::destination::Destination;
     use crate::core::ext::PathBufExt;
+    use crate::core::axml::Manifest;
+    use crate::core::config::{Config, RecordOptions, Toggle};
+    use crate::core::r#const::ANI_SCALE;
+    use crate::core::splits::DeviceSpec;
+    use crate::core::perms::parse_permissions;
+    use crate::core::install_error::InstallError;
+    use crate::core::flags::Flags;
+    use crate::core::launch::{LaunchOptions, OPTIONS, SWITCHES};
+    use crate::core::logview::{LogLine, THREADTIME};
+    use crate::core::signing::{sha256, signature};
+    use crate::core::mp4::{sps_dimensions, AnnexB, Mp4Writer};
+    use crate::core::template::unknown_placeholders;
     use crate::core::system::home_dir;
+    use crate::core::util::parse_duration;
+    use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
     fn destination() {
@@ -49,30 +64,320 @@ mod tests {
         }).collect::<Vec<String>>();
         assert_equal(output, expected);
     }
-}
-
-
-
-
-
-
-
-
-
-
 
+    #[test]
+    fn annex_b() {
+        let sps = vec![0x67, 0x42, 0xC0, 0x1F, 0xDA, 0x01, 0x40, 0x16, 0xE4];
+        let pps = vec![0x68, 0xCE, 0x3C, 0x80];
+        let idr = vec![0x65, 0x88, 0x84, 0x00, 0x00, 0x03, 0x00, 0x21];
+        let slice = vec![0x41, 0x9A, 0x02];
+        let stream = [
+            &[0, 0, 0, 1], sps.as_slice(),
+            &[0, 0, 1], pps.as_slice(),
+            &[0, 0, 0, 1], idr.as_slice(),
+            &[0, 0, 0, 1], slice.as_slice(),
+            &[0, 0, 0, 1],
+        ].concat();
+        let mut splitter = AnnexB::new();
+        let mut nals = vec![];
+        for chunk in stream.chunks(3) {
+            nals.append(&mut splitter.push(chunk));
+        }
+        assert_eq!(nals, vec![sps.clone(), pps.clone(), idr.clone(), slice]);
+        assert_eq!(sps_dimensions(&sps), Some((1280, 720)));
 
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = Mp4Writer::create(file.path()).unwrap();
+        let now = std::time::Instant::now();
+        for nal in [&[0x67], pps.as_slice(), &idr, &idr] {
+            writer.push_nal(nal, now).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
 
+    #[test]
+    fn record_options() {
+        let config = Config::default();
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(serde_yaml::from_str::<Config>(&yaml).unwrap(), config);
 
+        let args = ["--size", "720x1280", "--bugreport", "--bit-rate=2.5M"].map(String::from);
+        let options = config.screencasts.options.merge(&RecordOptions::parse(&args).unwrap());
+        assert_eq!(options.to_args(Some(30)).unwrap(), ["--bit-rate", "2500000", "--size", "720x1280", "--bugreport"]);
+        assert!(options.to_args(Some(22)).is_err());
+        let limit = RecordOptions { time_limit: Some(600), ..RecordOptions::default() };
+        assert!(limit.to_args(Some(30)).is_err());
+        assert!(limit.to_args(Some(34)).is_ok());
+        assert!(RecordOptions::parse(&["--foo".to_string()]).is_err());
+    }
 
+    #[test]
+    fn placeholders() {
+        let template = "~/Android/{model}/{package}/Screenshot_{n}_{foo}{}.png";
+        assert_eq!(unknown_placeholders(template), ["{foo}", "{}"]);
+        assert!(unknown_placeholders("Screenshot_%Y%m%d-%H%M%S.png").is_empty());
+    }
 
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10s"), Some(Duration::from_secs(10)));
+        assert_eq!(parse_duration("1.5m"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_duration("5d"), None);
+        assert_eq!(parse_duration("ms"), None);
+    }
 
+    #[test]
+    fn manifest() {
+        let strings = [
+            "name", "versionCode", "versionName", "minSdkVersion", "package", "enabled",
+            "manifest", "com.example", "1.2", "uses-sdk", "uses-permission", "android.permission.INTERNET",
+            "application", "activity", ".Hidden", "activity-alias", "Launcher", "intent-filter",
+            "action", "android.intent.action.MAIN", "category", "android.intent.category.LAUNCHER",
+        ];
+        let ids = [0x0101_0003, 0x0101_021b, 0x0101_021c, 0x0101_020c];
+        // element name index, attributes of (name index, type, data), None closes the element
+        let string = |name: u32, value: u32| (name, 0x03, value);
+        let elements = vec![
+            Some((6, vec![string(4, 7), (1, 0x10, 42), string(2, 8)])),
+            Some((9, vec![(3, 0x10, 21)])), None,
+            Some((10, vec![string(0, 11)])), None,
+            Some((12, vec![])),
+            Some((13, vec![string(0, 14), (5, 0x12, 0)])),
+            Some((17, vec![])),
+            Some((18, vec![string(0, 19)])), None,
+            Some((20, vec![string(0, 21)])), None,
+            None, None,
+            Some((15, vec![string(0, 16)])),
+            Some((17, vec![])),
+            Some((18, vec![string(0, 19)])), None,
+            Some((20, vec![string(0, 21)])), None,
+            None, None,
+            None, None,
+        ];
+        let manifest = Manifest::parse(&axml(&strings, &ids, &elements)).unwrap();
+        assert_eq!(manifest, Manifest {
+            package: "com.example".to_string(),
+            split: None,
+            version_code: Some(42),
+            version_name: Some("1.2".to_string()),
+            min_sdk: Some(21),
+            target_sdk: None,
+            compile_sdk: None,
+            permissions: vec!["android.permission.INTERNET".to_string()],
+            launchers: vec!["com.example.Launcher".to_string()],
+        });
+        assert!(Manifest::parse(&[0x03, 0x00, 0x08, 0x00]).is_err());
+    }
 
+    #[test]
+    fn splits() {
+        let spec = DeviceSpec {
+            abis: vec!["arm64_v8a".to_string(), "armeabi_v7a".to_string()],
+            density: Some(420),
+            languages: vec!["en".to_string(), "ru".to_string()],
+        };
+        let splits = [
+            None, Some("config.armeabi_v7a"), Some("config.arm64_v8a"), Some("config.x86"),
+            Some("config.xhdpi"), Some("config.xxhdpi"), Some("config.xxxhdpi"),
+            Some("config.en"), Some("config.de"), Some("config.fil"), Some("feature"), Some("feature.config.arm64_v8a"),
+        ];
+        let apks = splits.iter().map(|split| {
+            let manifest = Manifest { split: split.map(String::from), ..Manifest::default() };
+            (PathBuf::from(split.unwrap_or("base")), manifest)
+        }).collect();
+        let selected = spec.select(apks)
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(selected, ["base", "config.arm64_v8a", "config.xxhdpi", "config.en", "feature", "feature.config.arm64_v8a"]);
+    }
 
+    #[test]
+    fn permissions() {
+        let dump = "
+    requested permissions:
+      android.permission.INTERNET
+      android.permission.CAMERA
+    install permissions:
+      android.permission.INTERNET: granted=true
+    User 0: ceDataInode=1 installed=true hidden=false
+      runtime permissions:
+        android.permission.CAMERA: granted=false, flags=[ USER_SET|USER_FIXED ]
+        android.permission.POST_NOTIFICATIONS: granted=true
+    User 10: ceDataInode=2 installed=true hidden=false
+      runtime permissions:
+        android.permission.CAMERA: granted=true
+";
+        let permissions = parse_permissions(dump)
+            .into_iter()
+            .map(|it| (it.name, it.granted, it.runtime, it.flags))
+            .collect::<Vec<_>>();
+        assert_eq!(permissions, [
+            ("android.permission.INTERNET".to_string(), true, false, vec![]),
+            ("android.permission.CAMERA".to_string(), false, true, vec!["USER_SET".to_string(), "USER_FIXED".to_string()]),
+            ("android.permission.POST_NOTIFICATIONS".to_string(), true, true, vec![]),
+        ]);
+    }
 
+    #[test]
+    fn install_errors() {
+        let in_exp = [
+            ("adb: failed to install app.apk: Failure [INSTALL_FAILED_UPDATE_INCOMPATIBLE: Existing package app signatures do not match]", Some(InstallError::UpdateIncompatible)),
+            ("Failure [INSTALL_FAILED_VERSION_DOWNGRADE: Downgrade detected: Update version code 1 is older than current 2]", Some(InstallError::VersionDowngrade)),
+            ("Failure [INSTALL_FAILED_TEST_ONLY: installPackageLI]", Some(InstallError::TestOnly)),
+            ("Failure [INSTALL_PARSE_FAILED_NO_CERTIFICATES: No APK Signature Scheme v2 signature]", Some(InstallError::NoCertificates)),
+            ("Failure [INSTALL_PARSE_FAILED_BAD_MANIFEST: …]", Some(InstallError::ParseFailed("INSTALL_PARSE_FAILED_BAD_MANIFEST".to_string()))),
+            ("Failure [INSTALL_FAILED_CONFLICTING_PROVIDER: …]", Some(InstallError::Other("INSTALL_FAILED_CONFLICTING_PROVIDER".to_string()))),
+            ("adb: failed to stat app.apk: No such file or directory", None),
+        ];
+        for (input, expected) in in_exp {
+            assert_eq!(InstallError::parse(input), expected);
+        }
+    }
 
+    #[test]
+    fn launch_commands() {
+        let options = |args: &[&str]| {
+            let args = args.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+            LaunchOptions::from_flags(&Flags::parse(&args, &SWITCHES, &OPTIONS).unwrap()).unwrap()
+        };
+        let launcher = Some(".Main".to_string());
+        assert_eq!(
+            options(&["--deeplink", "app://home"]).start_command("com.app", launcher.clone()).unwrap(),
+            "am start -a android.intent.action.VIEW -d app://home -p com.app",
+        );
+        assert_eq!(
+            options(&["--deeplink", "app://home", "--activity", ".Links"]).start_command("com.app", launcher.clone()).unwrap(),
+            "am start -a android.intent.action.VIEW -d app://home -n com.app/com.app.Links",
+        );
+        assert_eq!(
+            options(&["--wait"]).start_command("com.app", launcher).unwrap(),
+            "am start -W -a android.intent.action.MAIN -n com.app/.Main",
+        );
+        assert_eq!(options(&[]).start_command("com.app", None), None);
+    }
 
+    #[test]
+    fn toggles() {
+        let config = Config::default();
+        assert_equal(config.toggles[ANI_SCALE].values(), ["0", "0.5", "1", "1.5", "2", "5", "10"]);
+        assert!(!serde_yaml::to_string(&config).unwrap().contains("toggles"));
+        let yaml = "get: settings get global wifi_on\nset:\n  on: svc wifi enable\n  off: svc wifi disable\n";
+        let toggle = serde_yaml::from_str::<Toggle>(yaml).unwrap();
+        assert_eq!((&toggle.post, toggle.symlink), (&None, false));
+        assert_equal(toggle.values(), ["off", "on"]);
+        let mut config = Config::default();
+        config.toggles.insert("wifi".to_string(), toggle);
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        let toggles = serde_yaml::from_str::<Config>(&yaml).unwrap().toggles;
+        assert_equal(toggles.keys(), ["wifi"]);
+    }
 
+    #[test]
+    fn log_lines() {
+        let regex = regex::Regex::new(THREADTIME).unwrap();
+        let line = LogLine::parse("01-02 12:34:56.789  1234  1250 W ActivityManager: Slow operation: 52ms", &regex);
+        assert_eq!(line, Some(LogLine {
+            time: "01-02 12:34:56.789".to_string(),
+            pid: 1234,
+            tid: 1250,
+            level: 'W',
+            tag: "ActivityManager".to_string(),
+            message: "Slow operation: 52ms".to_string(),
+        }));
+        let line = LogLine::parse("01-02 12:34:56.789 10012 10012 D my tag  : a: b", &regex).unwrap();
+        assert_eq!((line.tag.as_str(), line.message.as_str()), ("my tag", "a: b"));
+        assert_eq!(LogLine::parse("--------- beginning of main", &regex), None);
+    }
 
+    #[test]
+    fn signing_block() {
+        let prefixed = |bytes: &[u8]| [&(bytes.len() as u32).to_le_bytes()[..], bytes].concat();
+        let certificate = b"certificate";
+        let signed_data = [prefixed(&[]), prefixed(&prefixed(certificate))].concat();
+        let signer = prefixed(&[prefixed(&signed_data), prefixed(&[]), prefixed(&[])].concat());
+        let value = prefixed(&signer);
+        let pair = [&(value.len() as u64 + 4).to_le_bytes()[..], &0x7109_871a_u32.to_le_bytes(), &value].concat();
+        let size = (pair.len() + 24) as u64;
+        let block = [&size.to_le_bytes()[..], &pair, &size.to_le_bytes(), b"APK Sig Block 42"].concat();
+        let mut eocd = vec![0x50, 0x4b, 0x05, 0x06];
+        eocd.resize(16, 0);
+        eocd.extend((block.len() as u32).to_le_bytes());
+        eocd.extend([0, 0]);
+        let apk = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(apk.path(), [block, eocd].concat()).unwrap();
+        let signature = signature(apk.path()).unwrap().unwrap();
+        assert_eq!(signature.scheme, "v2");
+        assert_eq!(signature.sha256, sha256(certificate));
+    }
 
+    /// An element name index with the attributes of (name index, type, data), None closes the element.
+    type Element = Option<(u32, Vec<(u32, u8, u32)>)>;
 
+    fn axml(strings: &[&str], ids: &[u32], elements: &[Element]) -> Vec<u8> {
+        let u16 = |bytes: &mut Vec<u8>, value: u16| bytes.extend(value.to_le_bytes());
+        let u32 = |bytes: &mut Vec<u8>, value: u32| bytes.extend(value.to_le_bytes());
+        let mut data = vec![];
+        u32(&mut data, 0);
+        let mut offsets = vec![];
+        for string in strings {
+            offsets.push(data.len() as u32 - 4);
+            u16(&mut data, string.len() as u16);
+            string.encode_utf16().for_each(|it| u16(&mut data, it));
+            u16(&mut data, 0);
+        }
+        data.drain(..4);
+        data.resize(data.len().next_multiple_of(4), 0);
+        let mut body = vec![];
+        u16(&mut body, 0x0001);
+        u16(&mut body, 28);
+        u32(&mut body, (28 + offsets.len() * 4 + data.len()) as u32);
+        for value in [strings.len() as u32, 0, 0, 28 + strings.len() as u32 * 4, 0] {
+            u32(&mut body, value);
+        }
+        offsets.iter().for_each(|it| u32(&mut body, *it));
+        body.append(&mut data);
+        u16(&mut body, 0x0180);
+        u16(&mut body, 8);
+        u32(&mut body, 8 + ids.len() as u32 * 4);
+        ids.iter().for_each(|it| u32(&mut body, *it));
+        let mut open = vec![];
+        for element in elements {
+            let name = match element {
+                Some((name, _)) => *name,
+                None => open.pop().unwrap(),
+            };
+            let attrs = element.as_ref().map(|(_, attrs)| attrs.as_slice()).unwrap_or(&[]);
+            let start = element.is_some();
+            u16(&mut body, if start { 0x0102 } else { 0x0103 });
+            u16(&mut body, 16);
+            u32(&mut body, if start { 36 + attrs.len() as u32 * 20 } else { 24 });
+            for value in [0, u32::MAX, u32::MAX, name] {
+                u32(&mut body, value);
+            }
+            if !start {
+                continue
+            }
+            open.push(name);
+            for value in [20, 20, attrs.len() as u16, 0, 0, 0] {
+                u16(&mut body, value);
+            }
+            for (name, data_type, value) in attrs {
+                let raw = if *data_type == 0x03 { *value } else { u32::MAX };
+                for it in [u32::MAX, *name, raw] {
+                    u32(&mut body, it);
+                }
+                body.extend([8, 0, 0, *data_type]);
+                u32(&mut body, *value);
+            }
+        }
+        let mut bytes = vec![];
+        u16(&mut bytes, 0x0003);
+        u16(&mut bytes, 8);
+        u32(&mut bytes, 8 + body.len() as u32);
+        bytes.append(&mut body);
+        return bytes
+    }
+}